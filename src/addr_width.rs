@@ -21,12 +21,24 @@ LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
-#[derive(Copy, Clone, Debug, derive_more::Display, PartialEq, Eq)]
+
+//! Module for [`AddrWidth`].
+
+/// Width of a virtual address of a paging implementation.
+#[derive(Copy, Clone, Debug, derive_more::Display, PartialEq, Eq, serde::Serialize)]
+#[serde(into = "u64")]
 pub enum AddrWidth {
+    /// 32-bit addresses.
     #[display("32-bits")]
     Bits32,
+    /// 64-bit addresses.
     #[display("64-bits")]
     Bits64,
+    /// 128-bit addresses, for custom paging implementations of experimental
+    /// architectures. The helpers that operate on `u64` addresses treat them
+    /// like 64-bit addresses.
+    #[display("128-bits")]
+    Bits128,
 }
 
 impl From<AddrWidth> for u64 {
@@ -34,6 +46,169 @@ impl From<AddrWidth> for u64 {
         match value {
             AddrWidth::Bits32 => 32,
             AddrWidth::Bits64 => 64,
+            AddrWidth::Bits128 => 128,
+        }
+    }
+}
+
+impl AddrWidth {
+    /// Reinterprets the address as two's-complement signed integer of this
+    /// width. For example, `0xffffffff80000000` becomes `-2147483648` as
+    /// 64-bit value. The bits of the address above this width are ignored.
+    pub const fn to_signed(self, addr: u128) -> i128 {
+        match self {
+            AddrWidth::Bits32 => addr as u32 as i32 as i128,
+            AddrWidth::Bits64 => addr as u64 as i64 as i128,
+            AddrWidth::Bits128 => addr as i128,
+        }
+    }
+
+    /// Adds two addresses and wraps around at this width, as the CPU does when
+    /// it forms a linear address from a segment base and an offset.
+    pub const fn wrapping_add(self, a: u64, b: u64) -> u64 {
+        match self {
+            AddrWidth::Bits32 => (a as u32).wrapping_add(b as u32) as u64,
+            AddrWidth::Bits64 | AddrWidth::Bits128 => a.wrapping_add(b),
+        }
+    }
+
+    /// Returns the number of trailing zero bits of the address within this
+    /// width, i.e., the exponent of its alignment. Zero has as many trailing
+    /// zeros as the width has bits.
+    pub const fn trailing_zeros(self, addr: u64) -> u64 {
+        match self {
+            AddrWidth::Bits32 => (addr as u32).trailing_zeros() as u64,
+            AddrWidth::Bits64 | AddrWidth::Bits128 => addr.trailing_zeros() as u64,
+        }
+    }
+
+    /// Adds two addresses and returns `None` if the sum doesn't fit into this
+    /// width, such as when translating a physical address into the kernel's
+    /// direct map.
+    pub const fn checked_add(self, a: u64, b: u64) -> Option<u64> {
+        match self {
+            AddrWidth::Bits32 => match (a as u32).checked_add(b as u32) {
+                Some(sum) => Some(sum as u64),
+                None => None,
+            },
+            AddrWidth::Bits64 | AddrWidth::Bits128 => a.checked_add(b),
         }
     }
+
+    /// Returns whether the address fits into this width without truncation.
+    pub const fn fits(self, addr: u128) -> bool {
+        match self {
+            AddrWidth::Bits32 => addr <= u32::MAX as u128,
+            AddrWidth::Bits64 => addr <= u64::MAX as u128,
+            AddrWidth::Bits128 => true,
+        }
+    }
+
+    /// Truncates the address to this width, i.e., discards the upper bits.
+    pub const fn truncate(self, addr: u128) -> u128 {
+        match self {
+            AddrWidth::Bits32 => addr as u32 as u128,
+            AddrWidth::Bits64 => addr as u64 as u128,
+            AddrWidth::Bits128 => addr,
+        }
+    }
+
+    /// Returns the number of significant bits of the address within this
+    /// width, i.e., the position of the highest set bit plus one. A small
+    /// value for a 64-bit address often indicates that the wrong architecture
+    /// was chosen.
+    pub const fn significant_bits(self, addr: u128) -> u64 {
+        (u128::BITS - self.truncate(addr).leading_zeros()) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_signed() {
+        assert_eq!(
+            AddrWidth::Bits64.to_signed(0xffff_ffff_8000_0000),
+            -0x8000_0000
+        );
+        assert_eq!(AddrWidth::Bits64.to_signed(0x1000), 0x1000);
+        assert_eq!(AddrWidth::Bits32.to_signed(0xffff_f000), -0x1000);
+        assert_eq!(AddrWidth::Bits32.to_signed(0x1_0000_1000), 0x1000);
+        assert_eq!(
+            AddrWidth::Bits64.to_signed(0x10_0000_0000_0000_1000),
+            0x1000
+        );
+        assert_eq!(
+            AddrWidth::Bits128.to_signed(0x10_0000_0000_0000_1000),
+            0x10_0000_0000_0000_1000
+        );
+        assert_eq!(AddrWidth::Bits128.to_signed(u128::MAX), -1);
+    }
+
+    #[test]
+    fn test_significant_bits() {
+        assert_eq!(AddrWidth::Bits64.significant_bits(0xdead_beef), 32);
+        assert_eq!(AddrWidth::Bits64.significant_bits(0), 0);
+        assert_eq!(AddrWidth::Bits64.significant_bits(u64::MAX.into()), 64);
+        assert_eq!(AddrWidth::Bits64.significant_bits(1 << 64), 0);
+        assert_eq!(AddrWidth::Bits128.significant_bits(1 << 64), 65);
+        assert_eq!(AddrWidth::Bits32.significant_bits(0x1_0000_1000), 13);
+    }
+
+    #[test]
+    fn test_wrapping_add() {
+        assert_eq!(AddrWidth::Bits32.wrapping_add(0x1000, 0x20), 0x1020);
+        assert_eq!(AddrWidth::Bits32.wrapping_add(0xffff_f000, 0x2000), 0x1000);
+        assert_eq!(
+            AddrWidth::Bits64.wrapping_add(0xffff_f000, 0x2000),
+            0x1_0000_1000
+        );
+        assert_eq!(AddrWidth::Bits64.wrapping_add(u64::MAX, 0x21), 0x20);
+    }
+
+    #[test]
+    fn test_checked_add() {
+        assert_eq!(
+            AddrWidth::Bits64.checked_add(0xffff_8880_0000_0000, 0x1000),
+            Some(0xffff_8880_0000_1000)
+        );
+        assert_eq!(AddrWidth::Bits64.checked_add(u64::MAX, 1), None);
+        assert_eq!(
+            AddrWidth::Bits32.checked_add(0xc000_0000, 0x1000),
+            Some(0xc000_1000)
+        );
+        assert_eq!(
+            AddrWidth::Bits32.checked_add(0xc000_0000, 0x4000_0000),
+            None
+        );
+    }
+
+    #[test]
+    fn test_trailing_zeros() {
+        assert_eq!(AddrWidth::Bits64.trailing_zeros(0x1000), 12);
+        assert_eq!(AddrWidth::Bits64.trailing_zeros(0), 64);
+        assert_eq!(AddrWidth::Bits32.trailing_zeros(0), 32);
+        assert_eq!(AddrWidth::Bits32.trailing_zeros(0x1_0000_0000), 32);
+    }
+
+    #[test]
+    fn test_fits() {
+        assert!(AddrWidth::Bits32.fits(0xffff_ffff));
+        assert!(!AddrWidth::Bits32.fits(0x1_0000_0000));
+        assert!(AddrWidth::Bits64.fits(0x1_0000_0000));
+        assert!(AddrWidth::Bits64.fits(u64::MAX.into()));
+        assert!(!AddrWidth::Bits64.fits(1 << 64));
+        assert!(AddrWidth::Bits128.fits(u128::MAX));
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(AddrWidth::Bits32.truncate(0x1_dead_beef), 0xdead_beef);
+        assert_eq!(AddrWidth::Bits64.truncate(1 << 64 | 0x1000), 0x1000);
+        assert_eq!(
+            AddrWidth::Bits128.truncate(1 << 64 | 0x1000),
+            1 << 64 | 0x1000
+        );
+    }
 }
@@ -0,0 +1,93 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module for the batch mode, which processes one virtual address per line of
+//! an input file.
+
+use crate::cli::{parse_virtual_address, VirtualAddress, VirtualAddressError};
+
+/// Describes a line of the batch input that couldn't be parsed as
+/// [`VirtualAddress`].
+#[derive(Clone, Debug, derive_more::Display, PartialEq, Eq)]
+#[display("Line {line}: invalid address `{input}`: {error}")]
+pub struct BatchLineError {
+    /// Line number, starting at 1.
+    pub line: usize,
+    /// The content of the line.
+    pub input: String,
+    /// The reason why the line is invalid.
+    pub error: VirtualAddressError,
+}
+
+/// Parses one virtual address per line. Empty lines and lines starting with
+/// `#` are skipped. Invalid lines are skipped as well and reported in the
+/// returned errors, unless `fail_fast` is set. In that case, parsing stops at
+/// the first invalid line, so that no subsequent address is processed.
+pub fn parse_addresses(input: &str, fail_fast: bool) -> (Vec<VirtualAddress>, Vec<BatchLineError>) {
+    let mut v_addrs = vec![];
+    let mut errors = vec![];
+    for (i, line) in input.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match parse_virtual_address(line) {
+            Ok(v_addr) => v_addrs.push(v_addr),
+            Err(error) => {
+                errors.push(BatchLineError {
+                    line: i + 1,
+                    input: line.to_string(),
+                    error,
+                });
+                if fail_fast {
+                    break;
+                }
+            }
+        }
+    }
+    (v_addrs, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "# addresses\n0x1000\n\nfoo\n0x2000\n";
+
+    #[test]
+    fn test_parse_addresses_best_effort() {
+        let (v_addrs, errors) = parse_addresses(INPUT, false);
+        assert_eq!(v_addrs, vec![0x1000.into(), 0x2000.into()]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].line, 4);
+        assert_eq!(errors[0].input, "foo");
+    }
+
+    #[test]
+    fn test_parse_addresses_fail_fast() {
+        let (v_addrs, errors) = parse_addresses(INPUT, true);
+        assert_eq!(v_addrs, vec![0x1000.into()]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].error, VirtualAddressError::ParseIntError);
+    }
+}
@@ -22,99 +22,400 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use clap::{Parser, Subcommand, ValueEnum};
+use crate::mem_map::MemMap;
+use crate::pagemap::Pagemap;
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{Command, CommandFactory, Parser, Subcommand, ValueEnum};
+use clap_complete::Shell;
+use paging_calculator::paging_info::GenericPagingError;
+use paging_calculator::{impls, PagingImplInfo, Radix};
+pub use paging_calculator::{VirtualAddress, VirtualAddressError};
 use std::error::Error;
-use std::fmt;
+use std::io;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::OnceLock;
 
-/// A virtual address in hexadecimal representation. It be provided to the CLI
-/// as `0x123` or `0x1234_5678`. The `0x` prefix is required. It must be within
-/// the range of `u64`. Can be truncated to `u32`. In this case, the upper 32
-/// bits are discarded.
-#[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Ord, Eq, Hash)]
-pub struct VirtualAddress(u64);
+/// CLI args definition of this application for `clap`.
+#[derive(Parser)]
+// Subcommands terminate the list of virtual addresses.
+#[command(author, version, about, subcommand_precedence_over_arg = true)]
+pub struct CliArgs {
+    #[arg(value_parser = parse_virtual_address)]
+    /// One or more virtual addresses. They can be provided to the CLI in
+    /// hexadecimal as `0x123` or `0x1234_5678`, in binary with the `0b`
+    /// prefix, or in decimal without a prefix, unless `--radix` says
+    /// otherwise. At least one is required by all
+    /// architecture subcommands, such as `0x1000 0x2000 x86_64`.
+    pub virtual_addresses: Vec<VirtualAddress>,
 
-impl fmt::Display for VirtualAddress {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "0x{:016x}", self.0)
-    }
-}
+    /// Architecture/Paging implementation or utility command. Required unless
+    /// `--config` is given.
+    #[command(subcommand)]
+    pub command: Option<CliCommand>,
 
-impl VirtualAddress {
-    const PREFIX: &'static str = "0x";
-}
+    /// TOML file with the architecture and the virtual addresses, such as
+    /// `architecture = "x86_64"` and `addresses = ["0xdeadbeef"]`. Replaces
+    /// the virtual address and the architecture subcommand.
+    #[arg(long)]
+    pub config: Option<PathBuf>,
 
-/// Describes errors that happened when users tries to input a [`VirtualAddress`]
-/// via the CLI.
-#[derive(Copy, Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
-pub enum VirtualAddressError {
-    /// The virtual address must begin with the prefix 0x.
-    #[display("The virtual address must begin with the prefix 0x.")]
-    MissingPrefix,
-    /// The virtual address could not be parsed as number as `u64`
-    #[display("The virtual address could not be parsed as number as `u64`.")]
-    ParseIntError,
-}
+    #[arg(long, value_enum)]
+    pub color: Option<ColorOption>,
 
-impl Error for VirtualAddressError {}
+    /// Output format.
+    #[arg(long, global = true, value_enum)]
+    pub format: Option<OutputFormat>,
 
-impl From<u64> for VirtualAddress {
-    fn from(value: u64) -> Self {
-        Self(value)
-    }
-}
+    /// Key the levels in the JSON output by the names of the page tables, such
+    /// as `PML4`, instead of by their numbers. Implies `--format json`.
+    #[arg(long, global = true)]
+    pub json_named_levels: bool,
 
-impl From<VirtualAddress> for u64 {
-    fn from(value: VirtualAddress) -> Self {
-        value.0
-    }
-}
+    /// Emit the result as CSV in "long format" with one row per
+    /// (address, level) pair, suitable for `pandas.read_csv`.
+    #[arg(long, global = true)]
+    pub csv_long: bool,
 
-impl From<VirtualAddress> for u32 {
-    fn from(value: VirtualAddress) -> Self {
-        (value.0 & 0xffffffff) as Self
-    }
-}
+    /// Print a single line per address with the indices from the top level to
+    /// level 1 and the page offset, such as `L4=0x0 L3=0x3 ... off=0xeef`,
+    /// without descriptions or colors. Handy for shell scripts.
+    #[arg(long, global = true)]
+    pub compact: bool,
 
-impl FromStr for VirtualAddress {
-    type Err = VirtualAddressError;
+    /// Print only the page table indices in decimal, one per line, from the
+    /// top level to level 1. Unlike `--compact`, there are no labels and no
+    /// page offset.
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Remove underscores and other clutter which are allowed for the input.
-        let s = s.trim().to_lowercase().replace('_', "");
+    /// Omit the descriptive paragraph of the architecture from the text
+    /// output and only print the tables.
+    #[arg(long, global = true)]
+    pub brief: bool,
 
-        if !s.starts_with(Self::PREFIX) {
-            return Err(VirtualAddressError::MissingPrefix);
-        }
+    /// Verify that the page table indices of all levels, shifted into place,
+    /// and the page offset recompose the translated bits of the address.
+    /// Aborts on a mismatch, which indicates a bug. This applies to all output
+    /// formats, but only the text output confirms the verification.
+    #[arg(long, global = true)]
+    pub verify: bool,
 
-        // string without the prefix
-        let s_without_prefix = &s.as_str()[Self::PREFIX.len()..];
+    /// Print the address in binary once more as a single line in which the
+    /// index bits of each level have their own color, followed by the page
+    /// offset in gray.
+    #[arg(long, global = true)]
+    pub rainbow: bool,
 
-        u64::from_str_radix(s_without_prefix, 16)
-            .map(Self)
-            .map_err(|e| {
-                eprintln!("{e}");
-                VirtualAddressError::ParseIntError
-            })
-    }
-}
+    /// Radix of addresses without a prefix such as `0x`, e.g., `hex` to
+    /// accept bare hexadecimal addresses such as `deadbeef`. With `auto`, they
+    /// are decimal. With `hex`, `0b` is not a prefix, i.e., `0b1000` is
+    /// `0xb1000`. Applies to all address arguments, to `--batch`,
+    /// `--interactive`, and `--config`.
+    #[arg(
+        long,
+        global = true,
+        value_parser = PossibleValuesParser::new(["auto", "hex", "dec", "bin"])
+            .map(|radix| Radix::from_str(&radix).unwrap())
+    )]
+    pub radix: Option<Radix>,
 
-/// CLI args definition of this application for `clap`.
-#[derive(Parser)]
-#[command(author, version, about)]
-pub struct CliArgs {
-    #[arg()]
-    /// A virtual address in hexadecimal representation. It be provided to
-    /// the CLI as `0x123` or `0x1234_5678`. The `0x` prefix is required.
-    /// It must be within the range of `u64`.
-    pub virtual_address: VirtualAddress,
+    /// Print a ruler with the bit positions 63, 55, ..., 7, 0 above the
+    /// binary representation of the address.
+    #[arg(long, global = true)]
+    pub ruler: bool,
 
-    /// Architecture/Paging implementation.
-    #[command(subcommand)]
-    pub architecture: Architecture,
+    /// Additionally print the address reinterpreted as two's-complement signed
+    /// integer of the address width, e.g., higher-half addresses are negative.
+    #[arg(long, global = true)]
+    pub signed_decimal: bool,
 
-    #[arg(long, value_enum)]
-    pub color: Option<ColorOption>,
+    /// Comma-separated list of strides, e.g., `0x1000,0x200000`. For each
+    /// stride, the address plus the stride is walked as well and the levels
+    /// whose index changed relative to the address are shown.
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_parser = parse_virtual_address
+    )]
+    pub strides: Vec<VirtualAddress>,
+
+    /// Comma-separated list of page table indices, one per level from the top
+    /// level to level 1, e.g., `0x1ff,0x0,0x10,0x20`. Instead of a virtual
+    /// address, the address composed from these indices is walked.
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        value_parser = parse_virtual_address
+    )]
+    pub from_indices: Vec<VirtualAddress>,
+
+    /// Raw page table entry, e.g., from a page table dump. Instead of walking
+    /// an address, the virtual and physical address ranges that the entry
+    /// maps are printed. Requires `--pte-level` and `--pte-path`. Only
+    /// entries of the x86 architectures are decoded.
+    #[arg(
+        long,
+        global = true,
+        requires = "pte_level",
+        requires = "pte_path",
+        value_parser = parse_virtual_address
+    )]
+    pub pte: Option<VirtualAddress>,
+
+    /// Level of the page table that holds the entry of `--pte`, such as 1 for
+    /// a page table entry that maps a 4 KiB page.
+    #[arg(long, global = true, requires = "pte")]
+    pub pte_level: Option<u64>,
+
+    /// Comma-separated list of the indices that lead to the entry of `--pte`,
+    /// from the top level down to the index of the entry itself. The number of
+    /// indices must match `--pte-level`.
+    #[arg(
+        long,
+        global = true,
+        value_delimiter = ',',
+        requires = "pte",
+        value_parser = parse_virtual_address
+    )]
+    pub pte_path: Vec<VirtualAddress>,
+
+    /// Require underscores in hexadecimal numbers to delimit groups of exactly
+    /// four digits, such as `0xdead_beef`, to catch transcription errors.
+    #[arg(long, global = true)]
+    pub strict_grouping: bool,
+
+    /// Number of columns (entries per row) of a hex dump of a page table. If
+    /// set, the row and column of each entry within such a dump are shown.
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    pub dump_columns: Option<u64>,
+
+    /// Cache line size in bytes, such as `--cache-line-size=128`. If set, the
+    /// cache line within the page table that holds each entry is shown. Without
+    /// a value, the cache line size is 64 bytes.
+    #[arg(
+        long,
+        global = true,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "0x40",
+        value_parser = parse_page_size
+    )]
+    pub cache_line_size: Option<u64>,
+
+    /// Explain how the shift of each level is derived from the number of index
+    /// and page offset bits, such as `level 3 shift = 9*(3-1)+12 = 30`.
+    #[arg(long, global = true)]
+    pub explain: bool,
+
+    /// Treat the input address as physical address.
+    #[arg(long, global = true)]
+    pub physical: bool,
+
+    /// Treat the input number as page or physical frame number (PFN), i.e.,
+    /// as index of a page. It is multiplied by the page size to get the
+    /// address, whose page offset is always zero.
+    #[arg(long, global = true)]
+    pub pfn: bool,
+
+    /// Base address that the virtual address is relative to. The effective
+    /// address `base + address` is walked, such as `0x1020` for `--base
+    /// 0x1000` and the address `0x20`.
+    #[arg(long, global = true, value_parser = parse_virtual_address)]
+    pub base: Option<VirtualAddress>,
+
+    /// Memory map file to classify the physical address with. Each line has
+    /// the form `start end type`, where `start` and `end` are inclusive
+    /// hexadecimal addresses and `type` is `ram`, `device`, or `reserved`.
+    #[arg(long, global = true, requires = "physical", value_parser = MemMap::from_file)]
+    pub mem_map: Option<MemMap>,
+
+    /// Print the virtual page number (VPN), i.e., the concatenation of the
+    /// indices of all levels, as a single hexadecimal value.
+    #[arg(long, global = true)]
+    pub packed_vpn: bool,
+
+    /// File with one virtual address per line that are processed in batch
+    /// mode. Use `-` to read from stdin. Invalid lines are reported and
+    /// skipped.
+    #[arg(long, global = true)]
+    pub batch: Option<PathBuf>,
+
+    /// Read virtual addresses interactively from stdin, one per line, and
+    /// print the result for each address right away, until EOF. Invalid
+    /// addresses are reported and skipped.
+    #[arg(long, global = true, conflicts_with = "batch")]
+    pub interactive: bool,
+
+    /// Append the output of the virtual addresses to the given file instead
+    /// of printing it, so that the results of many runs can be collected.
+    /// The file is created if it doesn't exist. The output of `--pagemap`,
+    /// `--pte`, and the `translate`, `bounds`, `list`, and `man` subcommands
+    /// is still printed to stdout.
+    #[arg(long, global = true)]
+    pub output: Option<PathBuf>,
+
+    /// Stop the batch mode at the first invalid line instead of skipping it.
+    #[arg(long, global = true, requires = "batch")]
+    pub fail_fast: bool,
+
+    /// After the batch, print a text heatmap per level of how often the
+    /// indices were used, to reveal hotspots in the address space.
+    #[arg(long, global = true, requires = "batch")]
+    pub heatmap: bool,
+
+    /// On 32-bit architectures, label the reduction of the user input to 32
+    /// bits as wrap-around (as some hardware does) instead of truncation and
+    /// show the original value.
+    #[arg(long, global = true)]
+    pub wrap: bool,
+
+    /// Segment base that is added to the virtual address, which is then
+    /// treated as offset, to form the linear address that is walked (x86
+    /// segmentation). The sum wraps around at the address width, as paging is
+    /// only available in protected mode and long mode.
+    #[arg(long, global = true, value_parser = parse_virtual_address)]
+    pub segment_base: Option<VirtualAddress>,
+
+    /// Don't hint at huge-page sizes the address is aligned to.
+    #[arg(long, global = true)]
+    pub no_huge_page_hint: bool,
+
+    /// Print the running sum of the relevant address bits of each level, from
+    /// the top level down to the page offset. The final sum equals the address.
+    #[arg(long, global = true)]
+    pub running_sum: bool,
+
+    /// Print entry offsets both in hexadecimal and decimal, e.g., `0x18 (24)`.
+    /// Shorthand for `--offset-base both`.
+    #[arg(long, global = true, conflicts_with = "offset_base")]
+    pub offset_both: bool,
+
+    /// Base in which entry offsets are printed. Defaults to `hex`.
+    #[arg(long, global = true, value_enum)]
+    pub offset_base: Option<OffsetBase>,
+
+    /// Byte order of the page table dump to correlate the entry offsets with.
+    /// With `big`, the byte-swapped representation of each entry offset is
+    /// shown as well.
+    #[arg(long, global = true, value_enum)]
+    pub endian: Option<Endian>,
+
+    /// Notation of the address and the base addresses in the text output.
+    /// Without it, addresses are printed in lowercase hexadecimal.
+    #[arg(long, global = true, value_enum)]
+    pub addr_format: Option<AddrFormat>,
+
+    /// Physical address of the root page table, such as the value of CR3 on
+    /// x86. Prints the physical address of the entry that the walk reads from
+    /// the root table. Required by the `translate` subcommand.
+    #[arg(long, global = true, value_parser = parse_virtual_address)]
+    pub root: Option<VirtualAddress>,
+
+    /// Print the path of the page table walk as indented tree, from the root
+    /// table down to the page offset.
+    #[arg(long, global = true)]
+    pub tree: bool,
+
+    /// Print the page table walk as ASCII diagram of boxes and arrows, from
+    /// the root table down to the page, with the index at each arrow.
+    #[arg(long, global = true)]
+    pub diagram: bool,
+
+    /// Numbering convention of the printed page table levels. This only
+    /// relabels the output and doesn't change any computation.
+    #[arg(long, global = true, value_enum)]
+    pub level_numbering: Option<LevelNumbering>,
+
+    /// Order in which the levels are printed: From the root to the leaf level
+    /// (default) or the other way around, as some textbooks present it.
+    #[arg(long, global = true, value_enum)]
+    pub order: Option<LevelOrder>,
+
+    /// Index of the root table entry that maps the root table itself
+    /// (recursive self-map). Used by `--selfmap-level` and `--selfmap-frame`.
+    #[arg(long, global = true)]
+    pub recursive: Option<u64>,
+
+    /// Print the virtual address at which the page table of this level, that
+    /// the walk of the address uses, is accessible through the recursive
+    /// self-map at `--recursive`. The root table has the highest level.
+    #[arg(long, global = true, requires = "recursive")]
+    pub selfmap_level: Option<u64>,
+
+    /// Print the virtual address at which the page table in this physical
+    /// frame is accessible through the recursive self-map at `--recursive`.
+    /// The frame must hold one of the page tables that the walk of the address
+    /// reads from the memory image, which determines its level. Requires the
+    /// `translate` subcommand.
+    #[arg(
+        long,
+        global = true,
+        requires = "recursive",
+        value_parser = parse_virtual_address
+    )]
+    pub selfmap_frame: Option<VirtualAddress>,
+
+    /// Custom label of the page offset in the output, e.g., `section offset`
+    /// when modeling ARM 1 MiB sections. Defaults to `page offset`.
+    #[arg(long, global = true)]
+    pub page_offset_label: Option<String>,
+
+    /// Pagemap-derived file with one `virtual-address frame` pair per line,
+    /// such as extracted from `/proc/<pid>/pagemap`. Prints the page table
+    /// indices of each virtual address next to its frame for correlation.
+    #[arg(long, global = true, value_parser = Pagemap::from_file)]
+    pub pagemap: Option<Pagemap>,
+
+    /// Print a short stable hash of the page table indices of all levels.
+    /// Addresses in the same page share the hash, which is handy for
+    /// bucketing in scripts.
+    #[arg(long, global = true)]
+    pub walk_hash: bool,
+
+    /// Base of the kernel's direct map (physmap), as used by Xen or Linux.
+    /// The physical address is translated to the virtual address
+    /// `base + address`, which is then walked. Requires `--physical`.
+    #[arg(long, global = true, requires = "physical", value_parser = parse_virtual_address)]
+    pub directmap_base: Option<VirtualAddress>,
+
+    /// Print the number of levels with a non-zero index, i.e., how deep the
+    /// address reaches into the page table tree beyond the all-zero path.
+    #[arg(long, global = true)]
+    pub depth: bool,
+
+    /// Print a bitmask of the levels with a non-zero index. Bit `i` is set if
+    /// level `i + 1` (internal numbering) has a non-zero index, i.e., bit 0
+    /// corresponds to level 1. Handy for grouping addresses by the shape of
+    /// their walk.
+    #[arg(long, global = true)]
+    pub levels_mask: bool,
+
+    /// Compare the page table walk of the address with the walk of this
+    /// address side by side. Differing levels are highlighted. With two
+    /// addresses, such as `--diff 0xa000 0xb000`, these are compared and no
+    /// other virtual address may be given.
+    #[arg(long, global = true, num_args = 1..=2, value_parser = parse_virtual_address)]
+    pub diff: Vec<VirtualAddress>,
+
+    /// Print the number of trailing zero bits of the address, i.e., the
+    /// exponent of its alignment.
+    #[arg(long, global = true)]
+    pub trailing_zeros: bool,
+
+    /// Page size in bytes of a TLB entry, such as `0x200000` for 2 MiB. The
+    /// index bits that such a TLB entry treats as page offset are marked in
+    /// the bit diagram.
+    #[arg(long, global = true, value_parser = parse_page_size)]
+    pub tlb_page_size: Option<u64>,
+
+    /// Walk the base of the page that contains the address instead of the
+    /// address itself, i.e., the address with a page offset of zero. Handy
+    /// for faulting addresses.
+    #[arg(long, global = true)]
+    pub page_base: bool,
 }
 
 /// Whether colors and other ANSI escape sequences should be used.
@@ -130,6 +431,250 @@ pub enum ColorOption {
     Always,
 }
 
+/// Radix of addresses without a prefix, as given by `--radix`. Address
+/// arguments are parsed while clap parses the command line, so the radix is
+/// determined beforehand by [`scan_radix`].
+static INPUT_RADIX: OnceLock<Radix> = OnceLock::new();
+
+/// Returns the value of `--radix` from the raw command line arguments,
+/// including the binary name, if it is valid. Invalid values are reported by
+/// clap later on.
+pub fn scan_radix<I, S>(args: I) -> Option<Radix>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut args = args.into_iter().skip(1);
+    while let Some(arg) = args.next() {
+        let value = match arg.as_ref().strip_prefix("--radix") {
+            Some("") => args.next()?.as_ref().to_string(),
+            Some(value) => value.strip_prefix('=')?.to_string(),
+            None => continue,
+        };
+        return Radix::from_str(&value).ok();
+    }
+    None
+}
+
+/// Sets the radix of addresses without a prefix for all following parsing.
+/// Can only be set once.
+pub fn set_input_radix(radix: Radix) {
+    let _ = INPUT_RADIX.set(radix);
+}
+
+/// Parses a [`VirtualAddress`] with the radix of `--radix`.
+pub fn parse_virtual_address(input: &str) -> Result<VirtualAddress, VirtualAddressError> {
+    VirtualAddress::parse(input, INPUT_RADIX.get().copied().unwrap_or_default())
+}
+
+/// Parses a page size in bytes, in any notation accepted for a
+/// [`VirtualAddress`] and with the radix of `--radix`. The size must be a power
+/// of two.
+fn parse_page_size(input: &str) -> Result<u64, String> {
+    let size = parse_virtual_address(input)
+        .map(u64::from)
+        .map_err(|e| e.to_string())?;
+    if size.is_power_of_two() {
+        Ok(size)
+    } else {
+        Err(format!("The page size {size} is not a power of two."))
+    }
+}
+
+/// Output format of the page table walk.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq, Ord, Eq, Hash, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable text.
+    #[default]
+    Text,
+    /// One JSON object per address and line.
+    Json,
+    /// Rust `const` array of `(level, index, offset)` tuples per address,
+    /// e.g., to embed precomputed expectations into tests.
+    Rust,
+    /// Per address, the indices as one little-endian `u16` per level, from
+    /// the top level to level 1, as hex string.
+    Hex,
+    /// Like `hex`, but the raw bytes of all addresses without separators.
+    Binary,
+    /// Self-contained SVG diagram of the address fields per address.
+    Svg,
+    /// One S-expression per address and line, such as
+    /// `(walk (arch "x86_64 paging") (addr #xdeadbeef) (level 4 ...) ...)`.
+    Sexp,
+    /// CSV with a header row and one row per level, from the top level to
+    /// level 1. The rows of multiple addresses follow each other.
+    Csv,
+}
+
+/// Base in which entry offsets are printed.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq, Ord, Eq, Hash, ValueEnum)]
+pub enum OffsetBase {
+    /// Hexadecimal, such as `0x0018`.
+    #[default]
+    Hex,
+    /// Decimal, such as `24`.
+    Dec,
+    /// Hexadecimal followed by decimal, such as `0x18 (24)`.
+    Both,
+}
+
+/// Byte order of a machine, such as the one a page table dump is taken from.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq, Ord, Eq, Hash, ValueEnum)]
+pub enum Endian {
+    /// Least significant byte first, such as on x86.
+    #[default]
+    Little,
+    /// Most significant byte first.
+    Big,
+}
+
+/// Notation of addresses in the text output.
+#[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Ord, Eq, Hash, ValueEnum)]
+pub enum AddrFormat {
+    /// Lowercase hexadecimal with all digits of the address width, such as
+    /// `0xffff800000001000`.
+    Lower,
+    /// Uppercase hexadecimal with all digits of the address width, such as
+    /// `0xFFFF800000001000`.
+    Upper,
+    /// Uppercase hexadecimal in groups of 32 bits separated by a backtick, as
+    /// WinDbg does, such as `FFFF8000`00001000`.
+    Windbg,
+}
+
+/// Convention for numbering the levels of a page table walk.
+///
+/// For a walk with `n` levels, the mapping is:
+///
+/// | internal | Intel | ARM     |
+/// |----------|-------|---------|
+/// | `n`      | `n`   | `0`     |
+/// | ...      | ...   | ...     |
+/// | `1`      | `1`   | `n - 1` |
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq, Ord, Eq, Hash, ValueEnum)]
+pub enum LevelNumbering {
+    /// 1-based and bottom-up, i.e., level 1 is the leaf level. This is the
+    /// convention used internally.
+    #[default]
+    Internal,
+    /// Like Intel's manuals: The root is level `n`, such as level 4 (PML4) on
+    /// x86_64. This matches the internal convention.
+    Intel,
+    /// Like ARM's manuals: 0-based and top-down, i.e., the root is level 0.
+    Arm,
+}
+
+impl LevelNumbering {
+    /// Returns the label of the given internal (1-based, bottom-up) `level`
+    /// in a walk with `levels` levels.
+    pub const fn label(self, level: u64, levels: u64) -> u64 {
+        match self {
+            Self::Internal | Self::Intel => level,
+            Self::Arm => levels - level,
+        }
+    }
+}
+
+/// Order in which the levels of a page table walk are printed.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq, Ord, Eq, Hash, ValueEnum)]
+pub enum LevelOrder {
+    /// From the root, i.e., the top level, to level 1.
+    #[default]
+    TopDown,
+    /// From level 1 to the root.
+    BottomUp,
+}
+
+impl LevelOrder {
+    /// Orders the per-level items, which are given from level 1 to the top
+    /// level, such as the result of
+    /// [`PagingImplInfo::calc_page_table_lookup_meta_info`].
+    pub fn apply<T>(self, levels: &[T]) -> Vec<&T> {
+        match self {
+            Self::TopDown => levels.iter().rev().collect(),
+            Self::BottomUp => levels.iter().collect(),
+        }
+    }
+}
+
+/// Top-level subcommands of the CLI. Besides the architectures, which
+/// calculate the page table indices of the virtual address, there are a few
+/// utility commands.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Subcommand)]
+pub enum CliCommand {
+    #[command(flatten)]
+    Architecture(Architecture),
+    /// Print the minimal and maximal canonical virtual addresses of an
+    /// architecture. Doesn't take a virtual address.
+    Bounds {
+        /// Emit the bounds as JSON.
+        #[arg(long)]
+        json: bool,
+        /// Architecture/Paging implementation.
+        #[command(subcommand)]
+        architecture: Architecture,
+    },
+    /// Translate the virtual address to a physical address by walking the page
+    /// tables of a physical memory image. The walk stops at entries that are
+    /// not present and at huge pages.
+    Translate {
+        /// Raw physical memory image whose first byte is at physical address
+        /// zero.
+        #[arg(long)]
+        mem: PathBuf,
+        /// Architecture/Paging implementation.
+        #[command(subcommand)]
+        architecture: Architecture,
+    },
+    /// Calculate page table index information for a generic paging
+    /// implementation whose parameters are all given positionally. This is
+    /// handy for scripting. For example, `generic 64 4 9 12 8 0xdeadbeef`
+    /// equals `0xdeadbeef x86_64`.
+    Generic {
+        /// Address width in bits: `32` or `64`.
+        addr_width: u64,
+        /// Number of page-table levels.
+        levels: u64,
+        /// Number of bits used to index into a page table.
+        index_bits: u64,
+        /// Number of bits used to index into the page.
+        page_offset_bits: u64,
+        /// Size of a page table entry in bytes.
+        entry_size: u64,
+        /// The virtual address. It can be provided in hexadecimal as `0x123`
+        /// or `0x1234_5678`, in binary with the `0b` prefix, or in decimal
+        /// without a prefix, unless `--radix` says otherwise.
+        #[arg(value_parser = parse_virtual_address)]
+        address: VirtualAddress,
+    },
+    /// List all supported paging implementations and their parameters.
+    /// Doesn't take a virtual address.
+    List,
+    /// Render a man page (roff) of the CLI to stdout.
+    #[command(hide = true)]
+    Man,
+    /// Generate a shell completion script of the CLI to stdout.
+    #[command(hide = true)]
+    Completions {
+        /// Shell to generate the completion script for.
+        shell: Shell,
+    },
+}
+
+/// Renders the man page of the CLI in roff format into the given writer.
+pub fn render_man_page(out: &mut impl io::Write) -> io::Result<()> {
+    clap_mangen::Man::new(CliArgs::command()).render(out)
+}
+
+/// Renders the completion script of the CLI for the given shell into the
+/// given writer.
+pub fn render_completions(shell: Shell, out: &mut impl io::Write) {
+    let mut command = CliArgs::command();
+    let name = command.get_name().to_string();
+    clap_complete::generate(shell, &mut command, name, out);
+}
+
 /// Supported architectures with options. Each architecture is a subcommand of
 /// the CLI.
 #[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Ord, Eq, Hash, Subcommand)]
@@ -140,6 +685,15 @@ pub enum Architecture {
         /// Physical Page Extension.
         #[arg(long, default_value = "false")]
         pae: bool,
+        /// Page Size Extension (PSE), as also used by PSE-36. The page
+        /// directory maps 4 MiB pages directly, so only the page directory is
+        /// walked and the page offset has 22 bits.
+        #[arg(long, default_value = "false", conflicts_with = "pae")]
+        pse: bool,
+        /// Additionally compare the 2-level layout without PAE with the
+        /// 3-level layout with PAE for the address.
+        #[arg(long, default_value = "false")]
+        compare_pae: bool,
     },
     /// Calculate page table index information for x86_64. x86_64 uses a 4-level
     /// whose structure is similar to x86 with Page Address Extension (PAE) but
@@ -151,31 +705,582 @@ pub enum Architecture {
         /// `x86_64`.
         #[arg(short = '5', long, default_value = "false")]
         five_level: bool,
+        /// Size of the page that the address belongs to. For huge pages, the
+        /// page offset is larger and the walk stops at the level of the huge
+        /// page (level 2 for 2 MiB and level 3 for 1 GiB).
+        #[arg(long, value_enum, default_value_t = PageSize::Size4K)]
+        page_size: PageSize,
+    },
+    /// Calculate page table index information for a custom multi-level page
+    /// table (radix tree) with the given parameters, such as for experimental
+    /// MMU designs. All index and page offset bits must fit into the address
+    /// width.
+    Custom {
+        /// Address width in bits: `32`, `64`, or `128`.
+        #[arg(long, default_value = "64")]
+        addr_width: u64,
+        /// Number of page-table levels.
+        #[arg(long)]
+        levels: u64,
+        /// Number of bits used to index into a page table.
+        #[arg(long)]
+        index_bits: u64,
+        /// Number of bits used to index into the page.
+        #[arg(long)]
+        page_offset_bits: u64,
+        /// Size of a page table entry in bytes.
+        #[arg(long, default_value = "8")]
+        entry_size: u64,
     },
 }
 
+/// Describes that a string doesn't name a known [`Architecture`].
+#[derive(Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+#[display(
+    "Unknown architecture `{_0}`, expected one of `x86`, `x86-pae`, `x86_64`, or `x86_64-5level`."
+)]
+pub struct ArchitectureParseError(pub String);
+
+impl Error for ArchitectureParseError {}
+
+impl FromStr for Architecture {
+    type Err = ArchitectureParseError;
+
+    /// Parses the name of an architecture, independent of the CLI parser.
+    /// Names are case-insensitive and `-` and `_` are interchangeable. Known
+    /// aliases, such as `amd64` for `x86_64`, are accepted as well. Options
+    /// that are not part of the name get their default value.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace('-', "_");
+        let arch = match name.as_str() {
+            "x86" | "i386" | "i686" => Self::X86 {
+                pae: false,
+                pse: false,
+                compare_pae: false,
+            },
+            "x86_pae" => Self::X86 {
+                pae: true,
+                pse: false,
+                compare_pae: false,
+            },
+            "x86_64" | "amd64" | "x64" => Self::X86_64 {
+                five_level: false,
+                page_size: PageSize::default(),
+            },
+            "x86_64_5level" | "amd64_5level" | "x64_5level" => Self::X86_64 {
+                five_level: true,
+                page_size: PageSize::default(),
+            },
+            _ => return Err(ArchitectureParseError(s.to_string())),
+        };
+        Ok(arch)
+    }
+}
+
+/// Page sizes that are supported by x86_64.
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq, Ord, Eq, Hash, ValueEnum)]
+pub enum PageSize {
+    /// Regular 4 KiB page.
+    #[default]
+    #[value(name = "4k")]
+    Size4K,
+    /// 2 MiB huge page.
+    #[value(name = "2m")]
+    Size2M,
+    /// 1 GiB huge page.
+    #[value(name = "1g")]
+    Size1G,
+}
+
+impl PageSize {
+    /// Returns the size of the page in bytes.
+    pub const fn bytes(self) -> u64 {
+        match self {
+            Self::Size4K => 4 * 1024,
+            Self::Size2M => 2 * 1024 * 1024,
+            Self::Size1G => 1024 * 1024 * 1024,
+        }
+    }
+}
+
+impl Architecture {
+    /// Returns the size of the huge page in bytes, if the architecture is
+    /// configured to walk huge pages instead of regular pages.
+    pub const fn huge_page_size(self) -> Option<u64> {
+        match self {
+            Self::X86 { pse: true, .. } => Some(4 * 1024 * 1024),
+            Self::X86_64 { page_size, .. } if !matches!(page_size, PageSize::Size4K) => {
+                Some(page_size.bytes())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<Architecture> for PagingImplInfo {
+    type Error = GenericPagingError;
+
+    /// Builds the [`PagingImplInfo`] from the constant of the [`impls`] module
+    /// that matches the [`Architecture`] and adjusts it to the page size. For
+    /// [`Architecture::Custom`], it is constructed from the given parameters,
+    /// which may be invalid.
+    fn try_from(arch: Architecture) -> Result<Self, Self::Error> {
+        let info = match arch {
+            Architecture::X86 {
+                pae: false,
+                pse: false,
+                ..
+            } => impls::X86,
+            Architecture::X86 {
+                pae: false,
+                pse: true,
+                ..
+            } => impls::X86
+                .with_page_size(arch.huge_page_size().unwrap())
+                .expect("x86 should support 4 MiB pages"),
+            Architecture::X86 { pae: true, .. } => impls::X86_PAE,
+            Architecture::X86_64 {
+                five_level,
+                page_size,
+            } => {
+                let info = if five_level {
+                    impls::X86_64_5LEVEL
+                } else {
+                    impls::X86_64
+                };
+                info.with_page_size(page_size.bytes())
+                    .expect("x86_64 should support all page sizes")
+            }
+            Architecture::Custom {
+                addr_width,
+                levels,
+                index_bits,
+                page_offset_bits,
+                entry_size,
+            } => {
+                let info = PagingImplInfo::generic(
+                    addr_width,
+                    levels,
+                    index_bits,
+                    page_offset_bits,
+                    entry_size,
+                )?;
+                // Unlike `generic`, the top level must not be truncated.
+                if info.translated_bits() != levels * index_bits + page_offset_bits {
+                    return Err(GenericPagingError::ExceedsAddrWidth);
+                }
+                info
+            }
+        };
+        Ok(info)
+    }
+}
+
+/// Describes an architecture-specific flag that was passed to an architecture
+/// subcommand that doesn't know it, although another architecture does.
+#[derive(Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+#[display(
+    "The flag `{flag}` is not supported by `{used_arch}`. It belongs to `{expected_arch}`, \
+    e.g. `{expected_arch} {flag}`."
+)]
+pub struct ArchFlagMismatchError {
+    /// The flag as provided by the user, without a value.
+    pub flag: String,
+    /// The architecture subcommand the flag was passed to.
+    pub used_arch: String,
+    /// The architecture subcommand that supports the flag.
+    pub expected_arch: String,
+}
+
+impl Error for ArchFlagMismatchError {}
+
+/// Validates that architecture-specific flags are only used with architecture
+/// subcommands that support them. `args` are the raw CLI args, including the
+/// binary name. The check is derived from the `clap` definitions, so it
+/// automatically covers every flag of every architecture.
+///
+/// This gives users a tailored hint (such as "use `x86_64` for
+/// `--five-level`") instead of clap's generic "unexpected argument" error.
+pub fn validate_arch_flags<I, S>(args: I) -> Result<(), ArchFlagMismatchError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let root = CliArgs::command();
+    let mut parent = &root;
+    let mut current = &root;
+    for arg in args.into_iter().skip(1) {
+        let arg = arg.as_ref();
+        if let Some(subcommand) = current.find_subcommand(arg) {
+            parent = current;
+            current = subcommand;
+            continue;
+        }
+        if !arg.starts_with('-') || command_has_flag(current, arg) {
+            continue;
+        }
+        if let Some(expected) = parent
+            .get_subcommands()
+            .find(|sibling| command_has_flag(sibling, arg))
+        {
+            return Err(ArchFlagMismatchError {
+                flag: flag_name(arg).to_string(),
+                used_arch: current.get_name().to_string(),
+                expected_arch: expected.get_name().to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Returns the name of a flag without an optional `=value` suffix.
+fn flag_name(arg: &str) -> &str {
+    arg.split_once('=').map_or(arg, |(name, _)| name)
+}
+
+/// Returns whether the given command directly defines the given flag, either
+/// as long (`--flag`) or short (`-f`) flag.
+fn command_has_flag(cmd: &Command, arg: &str) -> bool {
+    let flag = flag_name(arg);
+    cmd.get_arguments().any(|a| {
+        a.get_long()
+            .is_some_and(|long| flag.strip_prefix("--") == Some(long))
+            || a.get_short()
+                .is_some_and(|short| flag.strip_prefix('-') == Some(short.encode_utf8(&mut [0; 4])))
+    })
+}
+
+/// Describes a hexadecimal number whose digits are not grouped into groups of
+/// four by underscores, although `--strict-grouping` requires it.
+#[derive(Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+#[display(
+    "The hexadecimal number `{_0}` must be grouped into 4 digits by underscores, \
+    e.g. `0xdead_beef`, as `--strict-grouping` is used."
+)]
+pub struct HexGroupingError(pub String);
+
+impl Error for HexGroupingError {}
+
+/// Validates that all hexadecimal numbers with underscores in the raw CLI args
+/// are grouped into groups of exactly four digits, counted from the right,
+/// such as `0x1_0000` or `0xdead_beef`. Values of `--flag=value` args and of
+/// comma-separated lists are checked individually. Numbers without
+/// underscores are always fine.
+pub fn validate_hex_grouping<I, S>(args: I) -> Result<(), HexGroupingError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    for arg in args {
+        let arg = arg.as_ref();
+        let value = arg.split_once('=').map_or(arg, |(_, value)| value);
+        for number in value.split(',') {
+            if !has_strict_hex_grouping(number.trim()) {
+                return Err(HexGroupingError(number.trim().to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Returns whether the input is either no hexadecimal number with
+/// underscores, or one that is strictly grouped into four digits.
+fn has_strict_hex_grouping(input: &str) -> bool {
+    let input = input.to_lowercase();
+    let Some(digits) = input.strip_prefix(VirtualAddress::HEX_PREFIX) else {
+        return true;
+    };
+    if !digits.contains('_') {
+        return true;
+    }
+    let mut groups = digits.split('_');
+    let first = groups.next().unwrap();
+    (1..=4).contains(&first.len()) && groups.all(|group| group.len() == 4)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
-    fn test_virtual_addr_from_str() {
-        assert_eq!(VirtualAddress::from_str("0x123"), Ok(0x123.into()));
+    fn test_validate_hex_grouping() {
+        for regular in [
+            "0xdead_beef",
+            "0x1_0000",
+            "0xffff_8000_0000_0000",
+            "0xdeadbeef",
+        ] {
+            assert_eq!(validate_hex_grouping([regular]), Ok(()), "{regular}");
+        }
+        assert_eq!(
+            validate_hex_grouping(["0xde_adbe_ef"]),
+            Err(HexGroupingError("0xde_adbe_ef".to_string()))
+        );
+        assert!(validate_hex_grouping(["0xdead_beef_"]).is_err());
+        assert!(validate_hex_grouping(["0x_dead_beef"]).is_err());
+        assert!(validate_hex_grouping(["0xdead_beefa"]).is_err());
+        // values of flags and lists
+        assert!(validate_hex_grouping(["x86_64", "--strides=0x1000,0x20_0000"]).is_ok());
+        assert_eq!(
+            validate_hex_grouping(["--strides=0x1000,0x200_000"]),
+            Err(HexGroupingError("0x200_000".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_level_numbering_label() {
+        assert_eq!(LevelNumbering::Internal.label(1, 4), 1);
+        assert_eq!(LevelNumbering::Intel.label(4, 4), 4);
+        // ARM: the root is level 0 and the leaf of a 4-level walk is level 3.
+        assert_eq!(LevelNumbering::Arm.label(4, 4), 0);
+        assert_eq!(LevelNumbering::Arm.label(1, 4), 3);
+    }
+
+    #[test]
+    fn test_level_order() {
+        let levels = impls::X86_64.calc_page_table_lookup_meta_info(0xdead_beef.into());
+        let order = |order: LevelOrder| {
+            order
+                .apply(&levels)
+                .iter()
+                .map(|info| info.level)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order(LevelOrder::TopDown), [4, 3, 2, 1]);
+        assert_eq!(order(LevelOrder::BottomUp), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_architecture_from_str() {
+        let x86 = Architecture::X86 {
+            pae: false,
+            pse: false,
+            compare_pae: false,
+        };
+        let x86_pae = Architecture::X86 {
+            pae: true,
+            pse: false,
+            compare_pae: false,
+        };
+        let x86_64 = Architecture::X86_64 {
+            five_level: false,
+            page_size: PageSize::Size4K,
+        };
+        let x86_64_5level = Architecture::X86_64 {
+            five_level: true,
+            page_size: PageSize::Size4K,
+        };
+        for (name, expected) in [
+            ("x86", x86),
+            ("i386", x86),
+            ("i686", x86),
+            ("x86-pae", x86_pae),
+            ("x86_pae", x86_pae),
+            ("x86_64", x86_64),
+            ("x86-64", x86_64),
+            ("X86_64", x86_64),
+            ("amd64", x86_64),
+            ("x64", x86_64),
+            ("x86_64-5level", x86_64_5level),
+            ("x86_64_5level", x86_64_5level),
+            ("amd64-5level", x86_64_5level),
+            ("x64-5level", x86_64_5level),
+        ] {
+            assert_eq!(Architecture::from_str(name), Ok(expected), "{name}");
+        }
         assert_eq!(
-            VirtualAddress::from_str("0xdead_beef"),
-            Ok(0xdead_beef.into())
+            Architecture::from_str("riscv64"),
+            Err(ArchitectureParseError("riscv64".to_string()))
         );
+    }
+
+    #[test]
+    fn test_parse_page_size() {
+        assert_eq!(parse_page_size("0x20_0000"), Ok(0x20_0000));
+        assert_eq!(parse_page_size("4096"), Ok(4096));
+        assert!(parse_page_size("0x3000").is_err());
+        assert_eq!(parse_page_size("2m"), Ok(0x20_0000));
+        assert!(parse_page_size("3k").is_err());
+    }
+
+    #[test]
+    fn test_cli_definition() {
+        CliArgs::command().debug_assert();
+    }
+
+    #[test]
+    fn test_validate_arch_flags() {
+        assert_eq!(validate_arch_flags(["pc", "0x1", "x86", "--pae"]), Ok(()));
+        assert_eq!(validate_arch_flags(["pc", "0x1", "x86_64", "-5"]), Ok(()));
+
+        let err = validate_arch_flags(["pc", "0x1", "x86", "--five-level"]).unwrap_err();
+        assert_eq!(err.expected_arch, "x86_64");
         assert_eq!(
-            VirtualAddress::from_str("    0xdEAd_bEEF    "),
-            Ok(0xdead_beef.into())
+            err.to_string(),
+            "The flag `--five-level` is not supported by `x86`. It belongs to `x86_64`, \
+            e.g. `x86_64 --five-level`."
         );
+
+        let err = validate_arch_flags(["pc", "0x1", "x86_64", "--pae=true"]).unwrap_err();
+        assert_eq!(err.flag, "--pae");
+        assert_eq!(err.expected_arch, "x86");
+    }
+
+    #[test]
+    fn test_render_man_page() {
+        let mut man_page = Vec::new();
+        render_man_page(&mut man_page).unwrap();
+        let man_page = String::from_utf8(man_page).unwrap();
+        assert!(man_page.contains(".TH"));
+        assert!(man_page.contains("paging-calculator"));
+    }
+
+    #[test]
+    fn test_render_completions() {
+        let mut completions = Vec::new();
+        render_completions(Shell::Bash, &mut completions);
+        let completions = String::from_utf8(completions).unwrap();
+        assert!(!completions.is_empty());
+        assert!(completions.contains("x86_64"));
+        assert!(completions.contains("x86"));
+        assert!(completions.contains("--color"));
     }
 
     #[test]
-    fn test_virtual_addr_64_to_32_bit() {
-        let v_addr = VirtualAddress::from_str("0xdead_beef_1337_1337");
-        assert_eq!(v_addr, Ok(0xdead_beef_1337_1337.into()));
-        let v_addr = v_addr.unwrap();
-        assert_eq!(u32::from(v_addr), 0x1337_1337);
+    fn test_multiple_addresses() {
+        let cli = CliArgs::parse_from(["pc", "0x1000", "0x2000", "x86_64"]);
+        assert_eq!(cli.virtual_addresses, [0x1000.into(), 0x2000.into()]);
+        assert!(matches!(cli.command, Some(CliCommand::Architecture(_))));
+    }
+
+    #[test]
+    fn test_scan_radix() {
+        assert_eq!(
+            scan_radix(["pc", "deadbeef", "--radix", "hex"]),
+            Some(Radix::Hex)
+        );
+        assert_eq!(scan_radix(["pc", "--radix=bin", "x86"]), Some(Radix::Bin));
+        assert_eq!(scan_radix(["pc", "--radix=dec"]), Some(Radix::Dec));
+        assert_eq!(scan_radix(["pc", "--radix", "auto"]), Some(Radix::Auto));
+        assert_eq!(scan_radix(["pc", "0x1000", "x86_64"]), None);
+        assert_eq!(scan_radix(["pc", "--radix", "oct"]), None);
+        assert_eq!(scan_radix(["pc", "--radix"]), None);
+        let cli = CliArgs::parse_from(["pc", "0x1000", "x86_64", "--radix", "hex"]);
+        assert_eq!(cli.radix, Some(Radix::Hex));
+    }
+
+    #[test]
+    fn test_diff_two_addresses() {
+        let cli = CliArgs::parse_from(["pc", "x86_64", "--diff", "0x1000", "0x2000"]);
+        assert!(cli.virtual_addresses.is_empty());
+        assert_eq!(cli.diff, [0x1000.into(), 0x2000.into()]);
+        assert!(CliArgs::try_parse_from(["pc", "x86_64", "--diff", "1", "2", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_pte_requires_level_and_path() {
+        let cli = CliArgs::parse_from([
+            "pc",
+            "x86_64",
+            "--pte",
+            "0x5003",
+            "--pte-level",
+            "1",
+            "--pte-path",
+            "0,0,0,1",
+        ]);
+        assert_eq!(cli.pte_level, Some(1));
+        assert_eq!(cli.pte_path.len(), 4);
+
+        assert!(
+            CliArgs::try_parse_from(["pc", "x86_64", "--pte", "0x5003", "--pte-path", "1"])
+                .is_err()
+        );
+        assert!(
+            CliArgs::try_parse_from(["pc", "x86_64", "--pte", "0x5003", "--pte-level", "1"])
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_pse() {
+        let cli = CliArgs::parse_from(["pc", "0xdeadbeef", "x86", "--pse"]);
+        let Some(CliCommand::Architecture(arch)) = cli.command else {
+            panic!("expected architecture subcommand");
+        };
+        let info = PagingImplInfo::try_from(arch).unwrap();
+        assert_eq!(info.levels, 2);
+        assert_eq!(info.leaf_level, 2);
+        assert_eq!(info.page_offset_bits, 22);
+        assert_eq!(info.page_table_index_bits, 10);
+        let infos = info.calc_page_table_lookup_meta_info(0xdead_beef.into());
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].level, 2);
+        assert_eq!(infos[0].index, 0x37a);
+        assert_eq!(info.page_offset(0xdead_beef.into()), 0x2d_beef);
+        assert_eq!(info.level_name(2), Some("PD"));
+
+        assert!(CliArgs::try_parse_from(["pc", "0x1", "x86", "--pse", "--pae"]).is_err());
+    }
+
+    #[test]
+    fn test_page_size() {
+        let cli = CliArgs::parse_from(["pc", "0x1", "x86_64", "--page-size", "2m"]);
+        let Some(CliCommand::Architecture(arch)) = cli.command else {
+            panic!("expected architecture subcommand");
+        };
+        let info = PagingImplInfo::try_from(arch).unwrap();
+        assert_eq!(info.page_offset_bits, 21);
+        assert_eq!(info.levels, 4);
+        assert_eq!(info.leaf_level, 2);
+
+        let cli = CliArgs::parse_from(["pc", "0x1", "x86_64", "-5", "--page-size", "1g"]);
+        let Some(CliCommand::Architecture(arch)) = cli.command else {
+            panic!("expected architecture subcommand");
+        };
+        let info = PagingImplInfo::try_from(arch).unwrap();
+        assert_eq!(info.page_offset_bits, 30);
+        assert_eq!(info.levels, 5);
+        assert_eq!(info.leaf_level, 3);
+    }
+
+    #[test]
+    fn test_custom_architecture() {
+        let cli = CliArgs::parse_from([
+            "pc",
+            "0xdeadbeef",
+            "custom",
+            "--levels",
+            "3",
+            "--index-bits",
+            "11",
+            "--page-offset-bits",
+            "12",
+        ]);
+        let Some(CliCommand::Architecture(arch)) = cli.command else {
+            panic!("expected architecture subcommand");
+        };
+        let info = PagingImplInfo::try_from(arch).unwrap();
+        assert_eq!(info.levels, 3);
+        assert_eq!(info.translated_bits(), 45);
+        let indices = info
+            .calc_page_table_lookup_meta_info(cli.virtual_addresses[0])
+            .iter()
+            .map(|info| info.index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, [0x2db, 0x1bd, 0x0]);
+
+        // 3 * 22 + 12 bits exceed the address width
+        let arch = Architecture::Custom {
+            addr_width: 64,
+            levels: 3,
+            index_bits: 22,
+            page_offset_bits: 12,
+            entry_size: 8,
+        };
+        assert_eq!(
+            PagingImplInfo::try_from(arch).unwrap_err(),
+            GenericPagingError::ExceedsAddrWidth
+        );
     }
 }
@@ -0,0 +1,157 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for the config file mode, which reads the architecture and the
+//! virtual addresses from a TOML file, such as:
+//!
+//! ```toml
+//! architecture = "x86_64"
+//! addresses = ["0xdeadbeef", "0xffff800000000000"]
+//! ```
+
+use crate::cli::{
+    parse_virtual_address, Architecture, ArchitectureParseError, VirtualAddress,
+    VirtualAddressError,
+};
+use serde::Deserialize;
+use std::error::Error;
+use std::str::FromStr;
+
+/// The config file as it is written. The addresses are strings, so that they
+/// can use the same notation as on the command line.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawConfig {
+    architecture: String,
+    addresses: Vec<String>,
+}
+
+/// The architecture and the virtual addresses of a config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Architecture/Paging implementation, given by its name.
+    pub architecture: Architecture,
+    /// Virtual addresses to calculate, in the order of the file.
+    pub addresses: Vec<VirtualAddress>,
+}
+
+/// Describes why a config file is invalid.
+#[derive(Clone, Debug, derive_more::Display, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The file isn't valid TOML or doesn't have the expected keys.
+    #[display("Invalid config: {_0}")]
+    Toml(String),
+    /// The architecture isn't known.
+    #[display("{_0}")]
+    Architecture(ArchitectureParseError),
+    /// An address can't be parsed.
+    #[display("Invalid address `{input}`: {error}")]
+    Address {
+        /// The address as written in the file.
+        input: String,
+        /// The reason why the address is invalid.
+        error: VirtualAddressError,
+    },
+    /// The list of addresses is empty.
+    #[display("The config doesn't contain any address.")]
+    NoAddresses,
+}
+
+impl Error for ConfigError {}
+
+impl FromStr for Config {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let raw = toml::from_str::<RawConfig>(s)
+            .map_err(|e| ConfigError::Toml(e.message().to_string()))?;
+        let architecture =
+            Architecture::from_str(&raw.architecture).map_err(ConfigError::Architecture)?;
+        if raw.addresses.is_empty() {
+            return Err(ConfigError::NoAddresses);
+        }
+        let addresses = raw
+            .addresses
+            .into_iter()
+            .map(|input| {
+                parse_virtual_address(&input).map_err(|error| ConfigError::Address { input, error })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            architecture,
+            addresses,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::PageSize;
+
+    const SAMPLE: &str = r#"
+# Teaching example: an address in the lower and in the higher half.
+architecture = "x86_64"
+addresses = ["0xdeadbeef", "0xffff_8000_0000_0000"]
+"#;
+
+    #[test]
+    fn test_parse_config() {
+        assert_eq!(
+            Config::from_str(SAMPLE),
+            Ok(Config {
+                architecture: Architecture::X86_64 {
+                    five_level: false,
+                    page_size: PageSize::Size4K,
+                },
+                addresses: vec![0xdead_beef.into(), 0xffff_8000_0000_0000.into()],
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        assert!(matches!(
+            Config::from_str("architecture = \"x86\""),
+            Err(ConfigError::Toml(_))
+        ));
+        assert_eq!(
+            Config::from_str("architecture = \"riscv64\"\naddresses = [\"0x1000\"]"),
+            Err(ConfigError::Architecture(ArchitectureParseError(
+                "riscv64".to_string()
+            )))
+        );
+        assert_eq!(
+            Config::from_str("architecture = \"x86\"\naddresses = []"),
+            Err(ConfigError::NoAddresses)
+        );
+        assert_eq!(
+            Config::from_str("architecture = \"x86\"\naddresses = [\"foo\"]"),
+            Err(ConfigError::Address {
+                input: "foo".to_string(),
+                error: VirtualAddressError::ParseIntError,
+            })
+        );
+    }
+}
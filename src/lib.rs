@@ -0,0 +1,67 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Library with the page table math of the `paging-calculator` CLI utility:
+//! Calculates the indices into the page tables from a virtual address. It
+//! knows multiple paging implementations, such as x86, x86 with PAE, x86_64 and
+//! x86_64 with a 5-level page table. See [`impls`] and [`PagingImplInfo`].
+//!
+//! ```
+//! use paging_calculator::{impls, VirtualAddress};
+//!
+//! let v_addr = VirtualAddress::from(0xdead_beef);
+//! let infos = impls::X86_64.calc_page_table_lookup_meta_info(v_addr);
+//! // the first element corresponds to level 1
+//! assert_eq!(infos[0].index, 219);
+//! ```
+
+#![deny(
+    clippy::all,
+    clippy::cargo,
+    clippy::nursery,
+    // clippy::restriction,
+    // clippy::pedantic
+)]
+// now allow a few rules which are denied by the above statement
+// --> they are ridiculous and not necessary
+#![allow(
+    clippy::suboptimal_flops,
+    clippy::redundant_pub_crate,
+    clippy::fallible_impl_from,
+    clippy::multiple_crate_versions
+)]
+// allow: required because of derive_more::Display macro
+#![allow(clippy::use_self)]
+#![deny(missing_docs)]
+#![deny(missing_debug_implementations)]
+#![deny(rustdoc::all)]
+
+pub mod addr_width;
+pub mod page_table_index;
+pub mod paging_info;
+mod virtual_address;
+
+pub use page_table_index::{calculate_page_table_index, PageTableLookupMetaInfo};
+pub use paging_info::{impls, PagingImplInfo};
+pub use virtual_address::{Radix, VirtualAddress, VirtualAddressError};
@@ -47,24 +47,642 @@ SOFTWARE.
 #![deny(missing_debug_implementations)]
 #![deny(rustdoc::all)]
 
-mod addr_width;
+mod batch;
 mod cli;
-mod page_table_index;
-mod paging_info;
+mod config;
+mod mem_map;
+mod pagemap;
 mod print;
+mod translate;
 
-use crate::cli::{CliArgs, ColorOption};
+use crate::cli::{Architecture, CliArgs, CliCommand, ColorOption, VirtualAddress};
+use crate::config::Config;
 use crate::print::USE_ANSI;
-use clap::Parser;
+use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
+use paging_calculator::impls;
+use paging_calculator::page_table_index::compose_virtual_address;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fs::OpenOptions;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::Path;
 use std::sync::atomic::Ordering;
+use std::{fs, io, process};
 
 fn main() {
+    // Give a tailored hint if an architecture-specific flag is used with the
+    // wrong architecture, before clap reports a generic error.
+    let args = std::env::args_os().map(|arg| arg.to_string_lossy().into_owned());
+    if let Err(e) = cli::validate_arch_flags(args) {
+        CliArgs::command()
+            .error(ErrorKind::ArgumentConflict, e)
+            .exit();
+    }
+
+    // Addresses are parsed by clap on the fly, so their radix must be known
+    // beforehand.
+    if let Some(radix) = cli::scan_radix(std::env::args()) {
+        cli::set_input_radix(radix);
+    }
+
     // parse the CLI args. parse() is generated by clap.
     let cli: CliArgs = CliArgs::parse();
 
+    if cli.strict_grouping {
+        if let Err(e) = cli::validate_hex_grouping(std::env::args().skip(1)) {
+            CliArgs::command()
+                .error(ErrorKind::ValueValidation, e)
+                .exit();
+        }
+    }
+
     configure_ansi_setting(cli.color.unwrap_or_default());
 
-    print::print(&cli);
+    if let Some(config) = &cli.config {
+        run_config(&cli, config);
+        return;
+    }
+
+    let Some(command) = &cli.command else {
+        CliArgs::command()
+            .error(
+                ErrorKind::MissingSubcommand,
+                "An architecture or utility subcommand is required unless `--config` is given.",
+            )
+            .exit();
+    };
+
+    match *command {
+        CliCommand::Architecture(architecture) => {
+            let paging_info = paging_info_of(architecture);
+            if let Architecture::Custom { .. } = architecture {
+                warn_if_table_doesnt_fill_page(&paging_info);
+            }
+            run_walk(&cli, &paging_info, cli.virtual_addresses.clone());
+        }
+        CliCommand::Generic {
+            addr_width,
+            levels,
+            index_bits,
+            page_offset_bits,
+            entry_size,
+            address,
+        } => {
+            if !cli.virtual_addresses.is_empty() {
+                CliArgs::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "The `generic` subcommand takes the virtual address as last parameter.",
+                    )
+                    .exit();
+            }
+            let paging_info = PagingImplInfo::generic(
+                addr_width,
+                levels,
+                index_bits,
+                page_offset_bits,
+                entry_size,
+            )
+            .unwrap_or_else(|e| {
+                CliArgs::command()
+                    .error(ErrorKind::ValueValidation, e)
+                    .exit()
+            });
+            warn_if_table_doesnt_fill_page(&paging_info);
+            run_walk(&cli, &paging_info, vec![address]);
+        }
+        CliCommand::Bounds { json, architecture } => {
+            if !cli.virtual_addresses.is_empty() {
+                CliArgs::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "The `bounds` subcommand doesn't take a virtual address.",
+                    )
+                    .exit();
+            }
+            print::print_bounds(&paging_info_of(architecture), json);
+        }
+        CliCommand::Translate {
+            ref mem,
+            architecture,
+        } => {
+            let Some(root) = cli.root else {
+                CliArgs::command()
+                    .error(
+                        ErrorKind::MissingRequiredArgument,
+                        "The `translate` subcommand requires the physical address of the root page table via `--root`.",
+                    )
+                    .exit();
+            };
+            let [v_addr] = cli.virtual_addresses[..] else {
+                CliArgs::command()
+                    .error(
+                        ErrorKind::MissingRequiredArgument,
+                        "Exactly one virtual address is required for the `translate` subcommand.",
+                    )
+                    .exit();
+            };
+            let image = fs::read(mem).unwrap_or_else(|e| {
+                CliArgs::command()
+                    .error(
+                        ErrorKind::Io,
+                        format!("Could not read the memory image: {e}"),
+                    )
+                    .exit()
+            });
+            let paging_info = paging_info_of(architecture);
+            validate_selfmap_args(&cli, &paging_info);
+            if let Some(warning) = print::truncation_warning(v_addr, paging_info.addr_width) {
+                eprintln!("{warning}");
+            }
+            let translation = translate::translate(&image, root.into(), &paging_info, v_addr);
+            if !print::print_translation(&paging_info, &translation) {
+                process::exit(1);
+            }
+            if let (Some(slot), Some(frame)) = (cli.recursive, cli.selfmap_frame) {
+                let frame = u64::from(frame);
+                let Some((addr, level)) = translate::selfmap_frame_address(
+                    &image,
+                    root.into(),
+                    &paging_info,
+                    v_addr,
+                    slot,
+                    frame,
+                ) else {
+                    eprintln!("The frame 0x{frame:x} doesn't hold a page table of the walk.");
+                    process::exit(1);
+                };
+                print::print_selfmap_frame(addr, level, slot);
+            }
+        }
+        CliCommand::List => {
+            if !cli.virtual_addresses.is_empty() {
+                CliArgs::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "The `list` subcommand doesn't take a virtual address.",
+                    )
+                    .exit();
+            }
+            print::print_list(impls::ALL);
+        }
+        CliCommand::Man => {
+            if let Err(e) = cli::render_man_page(&mut std::io::stdout().lock()) {
+                eprintln!("Failed to render the man page: {e}");
+                process::exit(1);
+            }
+        }
+        CliCommand::Completions { shell } => {
+            cli::render_completions(shell, &mut std::io::stdout().lock());
+        }
+    }
+}
+
+/// Walks the virtual addresses, the addresses of the batch file, or the
+/// addresses of the pagemap with the given paging implementation and prints
+/// the result.
+fn run_walk(cli: &CliArgs, paging_info: &PagingImplInfo, v_addrs: Vec<VirtualAddress>) {
+    validate_selfmap_args(cli, paging_info);
+    // With two addresses, `--diff` compares the first with the second one.
+    let v_addrs = if let [v_addr, _] = cli.diff[..] {
+        if !v_addrs.is_empty() {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "The virtual address can't be used together with two `--diff` addresses.",
+                )
+                .exit();
+        }
+        vec![v_addr]
+    } else {
+        v_addrs
+    };
+    if let Some(pte) = cli.pte {
+        if !v_addrs.is_empty() {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "The virtual address can't be used together with `--pte`.",
+                )
+                .exit();
+        }
+        if !paging_info.has_x86_entries() {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "`--pte` only decodes page table entries of the x86 architectures.",
+                )
+                .exit();
+        }
+        let path = cli
+            .pte_path
+            .iter()
+            .copied()
+            .map(u64::from)
+            .collect::<Vec<_>>();
+        let level = cli.pte_level.unwrap();
+        if !(paging_info.leaf_level..=paging_info.levels).contains(&level) {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!(
+                        "The entry level {level} is out of range, valid levels are {} to {}.",
+                        paging_info.leaf_level, paging_info.levels
+                    ),
+                )
+                .exit();
+        }
+        let expected = paging_info.levels - level + 1;
+        if path.len() as u64 != expected {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!(
+                        "The path of a level {level} entry needs {expected} indices, but has {}.",
+                        path.len()
+                    ),
+                )
+                .exit();
+        }
+        if let Err(e) = paging_info.entry_virtual_range(&path) {
+            CliArgs::command()
+                .error(ErrorKind::ValueValidation, e)
+                .exit();
+        }
+        print::print_pte(paging_info, pte.into(), level, &path);
+        return;
+    }
+    let v_addrs = if cli.from_indices.is_empty() {
+        v_addrs
+    } else {
+        if !v_addrs.is_empty() {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "The virtual address can't be used together with `--from-indices`.",
+                )
+                .exit();
+        }
+        let indices = cli
+            .from_indices
+            .iter()
+            .copied()
+            .map(u64::from)
+            .collect::<Vec<_>>();
+        let v_addr = compose_virtual_address(&indices, paging_info).unwrap_or_else(|e| {
+            CliArgs::command()
+                .error(ErrorKind::ValueValidation, e)
+                .exit()
+        });
+        vec![v_addr]
+    };
+    if let Some(pagemap) = &cli.pagemap {
+        if !v_addrs.is_empty() || cli.batch.is_some() {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "Neither the virtual address nor `--batch` can be used together with `--pagemap`.",
+                )
+                .exit();
+        }
+        print::print_pagemap(paging_info, pagemap);
+        return;
+    }
+    if cli.interactive {
+        if !v_addrs.is_empty() {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "The virtual address can't be used together with `--interactive`.",
+                )
+                .exit();
+        }
+        run_interactive(cli, paging_info);
+        return;
+    }
+    if let Some(batch) = &cli.batch {
+        if !v_addrs.is_empty() {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ArgumentConflict,
+                    "The virtual address can't be used together with `--batch`.",
+                )
+                .exit();
+        }
+        run_batch(cli, paging_info, batch);
+        return;
+    }
+    if v_addrs.is_empty() {
+        CliArgs::command()
+            .error(
+                ErrorKind::MissingRequiredArgument,
+                "At least one virtual address is required for architecture subcommands.",
+            )
+            .exit();
+    }
+    print_addresses(cli, paging_info, v_addrs);
+}
+
+/// Applies the address transformations of the CLI args, such as `--pfn`, to
+/// the user-provided addresses and prints the result.
+fn print_addresses(cli: &CliArgs, paging_info: &PagingImplInfo, v_addrs: Vec<VirtualAddress>) {
+    let v_addrs = apply_pfn(cli, paging_info, v_addrs);
+    let v_addrs = apply_base(cli, paging_info, v_addrs);
+    let v_addrs = apply_directmap_base(cli, paging_info, v_addrs);
+    let v_addrs = apply_segment_base(cli, paging_info, v_addrs);
+    let v_addrs = apply_page_base(cli, paging_info, v_addrs);
+    let result = cli.output.as_ref().map_or_else(
+        || print::print(&mut io::stdout().lock(), cli, paging_info, &v_addrs),
+        |output| {
+            let file = OpenOptions::new().create(true).append(true).open(output)?;
+            let mut file = BufWriter::new(file);
+            print::print(&mut file, cli, paging_info, &v_addrs)?;
+            file.flush()
+        },
+    );
+    if let Err(e) = result {
+        CliArgs::command()
+            .error(ErrorKind::Io, format!("Could not write the output: {e}"))
+            .exit();
+    }
+}
+
+/// Runs the config file mode: Reads the architecture and the virtual addresses
+/// from the TOML file and prints the result for all addresses.
+fn run_config(cli: &CliArgs, config: &Path) {
+    if !cli.virtual_addresses.is_empty() || cli.command.is_some() {
+        CliArgs::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "Neither the virtual address nor a subcommand can be used together with `--config`.",
+            )
+            .exit();
+    }
+    let input = fs::read_to_string(config).unwrap_or_else(|e| {
+        CliArgs::command()
+            .error(
+                ErrorKind::Io,
+                format!("Could not read {}: {e}", config.display()),
+            )
+            .exit()
+    });
+    let config = input.parse::<Config>().unwrap_or_else(|e| {
+        CliArgs::command()
+            .error(ErrorKind::ValueValidation, e)
+            .exit()
+    });
+    let paging_info = paging_info_of(config.architecture);
+    validate_selfmap_args(cli, &paging_info);
+    print_addresses(cli, &paging_info, config.addresses);
+}
+
+/// Builds the [`PagingImplInfo`] of the architecture. Exits with an error if
+/// the parameters of a custom architecture are invalid.
+fn paging_info_of(architecture: Architecture) -> PagingImplInfo {
+    PagingImplInfo::try_from(architecture).unwrap_or_else(|e| {
+        CliArgs::command()
+            .error(ErrorKind::ValueValidation, e)
+            .exit()
+    })
+}
+
+/// Warns on stderr if a page table of the paging implementation doesn't
+/// exactly fill a page, which is valid but unusual.
+fn warn_if_table_doesnt_fill_page(paging_info: &PagingImplInfo) {
+    if !paging_info.table_fills_page() {
+        eprintln!(
+            "Warning: A page table of {} entries of {} bytes doesn't exactly fill a page of {} bytes.",
+            paging_info.entries_per_table(),
+            paging_info.page_table_entry_size,
+            1_u64 << paging_info.page_offset_bits
+        );
+    }
+}
+
+/// Ensures that the recursive self-map slot and the requested level fit the
+/// page table hierarchy of the architecture.
+fn validate_selfmap_args(cli: &CliArgs, paging_info: &PagingImplInfo) {
+    if let Some(slot) = cli.recursive {
+        let entries = 1_u128 << paging_info.index_bits_at_level(paging_info.levels);
+        if u128::from(slot) >= entries {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!(
+                        "The recursive slot {slot} is out of range, the root table has {entries} entries."
+                    ),
+                )
+                .exit();
+        }
+    }
+    if cli.selfmap_frame.is_some() && !matches!(cli.command, Some(CliCommand::Translate { .. })) {
+        CliArgs::command()
+            .error(
+                ErrorKind::ArgumentConflict,
+                "`--selfmap-frame` requires the page tables of the `translate` subcommand.",
+            )
+            .exit();
+    }
+    if let Some(level) = cli.selfmap_level {
+        if !(paging_info.leaf_level..=paging_info.levels).contains(&level) {
+            CliArgs::command()
+                .error(
+                    ErrorKind::ValueValidation,
+                    format!(
+                        "The self-map level {level} is out of range, valid levels are {} to {}.",
+                        paging_info.leaf_level, paging_info.levels
+                    ),
+                )
+                .exit();
+        }
+    }
+}
+
+/// Turns the frame numbers into the base addresses of the frames, if `--pfn`
+/// is given. Exits with an error if an address doesn't fit into the address
+/// width of the architecture.
+fn apply_pfn(
+    cli: &CliArgs,
+    paging_info: &PagingImplInfo,
+    addrs: Vec<VirtualAddress>,
+) -> Vec<VirtualAddress> {
+    if !cli.pfn {
+        return addrs;
+    }
+    addrs
+        .into_iter()
+        .map(|pfn| {
+            paging_info
+                .frame_address(u64::from(pfn))
+                .map(VirtualAddress::from)
+                .unwrap_or_else(|| {
+                    CliArgs::command()
+                        .error(
+                            ErrorKind::ValueValidation,
+                            format!(
+                                "The address of frame number {pfn} overflows the address space ({}).",
+                                paging_info.addr_width
+                            ),
+                        )
+                        .exit()
+                })
+        })
+        .collect()
+}
+
+/// Adds the base to the addresses, if `--base` is given. Exits with an error if
+/// an address doesn't fit into the address width of the architecture.
+fn apply_base(
+    cli: &CliArgs,
+    paging_info: &PagingImplInfo,
+    addrs: Vec<VirtualAddress>,
+) -> Vec<VirtualAddress> {
+    let Some(base) = cli.base else {
+        return addrs;
+    };
+    let addr_width = paging_info.addr_width;
+    addrs
+        .into_iter()
+        .map(|addr| {
+            base.checked_add(addr)
+                .filter(|addr| addr_width.fits(addr.as_u128()))
+                .unwrap_or_else(|| {
+                    CliArgs::command()
+                        .error(
+                            ErrorKind::ValueValidation,
+                            format!(
+                                "The address {base} + {addr} overflows the address space ({addr_width})."
+                            ),
+                        )
+                        .exit()
+                })
+        })
+        .collect()
+}
+
+/// Translates the physical addresses to virtual addresses in the direct map,
+/// if `--directmap-base` is given. Exits with an error if an address doesn't
+/// fit into the address width of the architecture.
+fn apply_directmap_base(
+    cli: &CliArgs,
+    paging_info: &PagingImplInfo,
+    addrs: Vec<VirtualAddress>,
+) -> Vec<VirtualAddress> {
+    let Some(base) = cli.directmap_base else {
+        return addrs;
+    };
+    let addr_width = paging_info.addr_width;
+    addrs
+        .into_iter()
+        .map(|addr| {
+            addr_width
+                .checked_add(u64::from(base), u64::from(addr))
+                .map(VirtualAddress::from)
+                .unwrap_or_else(|| {
+                    CliArgs::command()
+                        .error(
+                            ErrorKind::ValueValidation,
+                            format!(
+                                "The direct-map address of {addr} overflows the address space ({addr_width})."
+                            ),
+                        )
+                        .exit()
+                })
+        })
+        .collect()
+}
+
+/// Forms the linear addresses from the segment base and the addresses as
+/// offsets, if `--segment-base` is given. The sum wraps around at the address
+/// width.
+fn apply_segment_base(
+    cli: &CliArgs,
+    paging_info: &PagingImplInfo,
+    addrs: Vec<VirtualAddress>,
+) -> Vec<VirtualAddress> {
+    let Some(base) = cli.segment_base else {
+        return addrs;
+    };
+    let addr_width = paging_info.addr_width;
+    addrs
+        .into_iter()
+        .map(|offset| {
+            addr_width
+                .wrapping_add(u64::from(base), u64::from(offset))
+                .into()
+        })
+        .collect()
+}
+
+/// Aligns the addresses down to the base of their page, if `--page-base` is
+/// given.
+fn apply_page_base(
+    cli: &CliArgs,
+    paging_info: &PagingImplInfo,
+    addrs: Vec<VirtualAddress>,
+) -> Vec<VirtualAddress> {
+    if !cli.page_base {
+        return addrs;
+    }
+    addrs
+        .into_iter()
+        .map(|addr| paging_info.page_base(addr))
+        .collect()
+}
+
+/// Runs the batch mode: Reads one virtual address per line from the given file
+/// (or stdin for `-`) and prints the result for all valid addresses. Invalid
+/// lines are reported on stderr. Exits with a non-zero exit code if there were
+/// invalid lines.
+fn run_batch(cli: &CliArgs, paging_info: &PagingImplInfo, batch: &Path) {
+    let input = if batch == Path::new("-") {
+        io::read_to_string(io::stdin())
+    } else {
+        fs::read_to_string(batch)
+    };
+    let input = input.unwrap_or_else(|e| {
+        CliArgs::command()
+            .error(
+                ErrorKind::Io,
+                format!("Could not read {}: {e}", batch.display()),
+            )
+            .exit()
+    });
+
+    // With `--fail-fast`, this only contains the addresses before the first
+    // invalid line.
+    let (v_addrs, errors) = batch::parse_addresses(&input, cli.fail_fast);
+    print_addresses(cli, paging_info, v_addrs);
+    for error in &errors {
+        eprintln!("{error}");
+    }
+    if !errors.is_empty() {
+        process::exit(1);
+    }
+}
+
+/// Runs the interactive mode: Prompts for one virtual address per line on
+/// stdin and prints the result for each address right away, until EOF.
+/// Invalid addresses are reported on stderr and don't end the session.
+fn run_interactive(cli: &CliArgs, paging_info: &PagingImplInfo) {
+    let mut line = String::new();
+    loop {
+        print!("> ");
+        io::stdout().flush().unwrap();
+        line.clear();
+        if matches!(io::stdin().read_line(&mut line), Ok(0) | Err(_)) {
+            break;
+        }
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match cli::parse_virtual_address(line) {
+            Ok(v_addr) => print_addresses(cli, paging_info, vec![v_addr]),
+            Err(e) => eprintln!("Invalid address `{line}`: {e}"),
+        }
+    }
+    println!();
 }
 
 /// Sets the global variable [`USE_ANSI`] depending on the value of
@@ -86,10 +704,50 @@ fn configure_ansi_setting(cfg: ColorOption) {
 /// Performs the auto-detection to see if stdout points to a TTY. If this is
 /// the case, I expect that ANSI escape sequences are supported.
 fn ansi_auto_detection() -> bool {
-    atty::is(atty::Stream::Stdout)
+    io::stdout().is_terminal()
 }
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+
+    #[test]
+    fn test_apply_base() {
+        let cli = CliArgs::parse_from(["paging-calculator", "0x20", "--base", "0x1000", "x86_64"]);
+        let v_addrs = apply_base(&cli, &impls::X86_64, vec![0x20.into()]);
+        assert_eq!(v_addrs, vec![0x1020.into()]);
+        let indices = impls::X86_64
+            .calc_page_table_lookup_meta_info(v_addrs[0])
+            .iter()
+            .map(|info| info.index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, [1, 0, 0, 0]);
+    }
+
+    #[test]
+    fn test_apply_segment_base() {
+        configure_ansi_setting(ColorOption::Never);
+        let args = [
+            "paging-calculator",
+            "0x20",
+            "x86",
+            "--segment-base",
+            "0x1000",
+        ];
+        let v_addrs = vec![0x20.into()];
+
+        let cli = CliArgs::parse_from(args.into_iter().chain(["--compact"]));
+        let v_addrs = apply_segment_base(&cli, &impls::X86, v_addrs);
+        assert_eq!(v_addrs, vec![0x1020.into()]);
+        let mut out = Vec::new();
+        print::print(&mut out, &cli, &impls::X86, &v_addrs).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "L2=0x0 L1=0x1 off=0x20\n");
+
+        let cli = CliArgs::parse_from(args.into_iter().chain(["--format", "json"]));
+        let mut out = Vec::new();
+        print::print(&mut out, &cli, &impls::X86, &v_addrs).unwrap();
+        assert!(String::from_utf8(out)
+            .unwrap()
+            .contains("\"address\":\"0x1020\""));
+    }
 }
@@ -0,0 +1,201 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Module to classify physical addresses with a memory map, i.e., a list of
+//! physical address ranges and their memory type.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::Radix;
+use std::error::Error;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+
+/// Type of a physical memory range.
+#[derive(Copy, Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum MemoryType {
+    /// Usable RAM.
+    #[display("RAM")]
+    Ram,
+    /// Memory-mapped device memory (MMIO).
+    #[display("device")]
+    Device,
+    /// Reserved memory.
+    #[display("reserved")]
+    Reserved,
+}
+
+impl FromStr for MemoryType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ram" => Ok(Self::Ram),
+            "device" => Ok(Self::Device),
+            "reserved" => Ok(Self::Reserved),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Describes errors that happened when reading or parsing a [`MemMap`].
+#[derive(Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum MemMapError {
+    /// The memory map file could not be read.
+    #[display("Could not read the memory map: {_0}")]
+    Io(String),
+    /// A line doesn't consist of exactly three columns.
+    #[display("Line {_0}: expected `start end type`.")]
+    InvalidLine(usize),
+    /// The start or end address could not be parsed.
+    #[display("Line {_0}: invalid address.")]
+    InvalidAddress(usize),
+    /// The start address is bigger than the end address.
+    #[display("Line {_0}: the start address is bigger than the end address.")]
+    InvalidRange(usize),
+    /// The memory type is not one of `ram`, `device`, or `reserved`.
+    #[display("Line {_0}: unknown memory type, expected `ram`, `device`, or `reserved`.")]
+    UnknownType(usize),
+}
+
+impl Error for MemMapError {}
+
+/// A single range of a [`MemMap`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct MemMapEntry {
+    /// The inclusive range of physical addresses.
+    pub range: RangeInclusive<u64>,
+    /// Type of the memory in that range.
+    pub memory_type: MemoryType,
+}
+
+/// A memory map describing the type of physical address ranges.
+///
+/// The textual representation consists of one `start end type` line per range,
+/// where `start` and `end` are inclusive hexadecimal addresses and `type` is
+/// one of `ram`, `device`, or `reserved`. Empty lines and lines starting with
+/// `#` are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct MemMap {
+    entries: Vec<MemMapEntry>,
+}
+
+impl MemMap {
+    /// Reads and parses a memory map from the file at the given path.
+    pub fn from_file(path: &str) -> Result<Self, MemMapError> {
+        fs::read_to_string(path)
+            .map_err(|e| MemMapError::Io(e.to_string()))?
+            .parse()
+    }
+
+    /// Returns the memory type of the given physical address, if any range of
+    /// the memory map contains it. The first matching range wins.
+    pub fn classify(&self, phys_addr: u64) -> Option<MemoryType> {
+        self.entries
+            .iter()
+            .find(|entry| entry.range.contains(&phys_addr))
+            .map(|entry| entry.memory_type)
+    }
+}
+
+impl FromStr for MemMap {
+    type Err = MemMapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = vec![];
+        for (i, line) in s.lines().enumerate() {
+            let line_nr = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            let [start, end, memory_type] = columns[..] else {
+                return Err(MemMapError::InvalidLine(line_nr));
+            };
+            let parse_addr = |addr: &str| {
+                VirtualAddress::parse(addr, Radix::Hex)
+                    .map(u64::from)
+                    .map_err(|_| MemMapError::InvalidAddress(line_nr))
+            };
+            let (start, end) = (parse_addr(start)?, parse_addr(end)?);
+            if start > end {
+                return Err(MemMapError::InvalidRange(line_nr));
+            }
+            let memory_type =
+                MemoryType::from_str(memory_type).map_err(|_| MemMapError::UnknownType(line_nr))?;
+            entries.push(MemMapEntry {
+                range: start..=end,
+                memory_type,
+            });
+        }
+        Ok(Self { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mem_map_classify() {
+        let mem_map = MemMap::from_str(
+            "# start end type\n\
+            0x0 0x9_ffff ram\n\
+            \n\
+            0xfee0_0000 0xfee0_0fff device\n\
+            0x10_0000 0xbfff_ffff RAM\n",
+        )
+        .unwrap();
+        assert_eq!(mem_map.classify(0x1000), Some(MemoryType::Ram));
+        assert_eq!(mem_map.classify(0xfee0_0020), Some(MemoryType::Device));
+        assert_eq!(mem_map.classify(0xa_0000), None);
+    }
+
+    #[test]
+    fn test_mem_map_bare_hex() {
+        let mem_map = MemMap::from_str("1000 2000 device").unwrap();
+        assert_eq!(mem_map.classify(0x1800), Some(MemoryType::Device));
+        assert_eq!(mem_map.classify(1500), None);
+    }
+
+    #[test]
+    fn test_mem_map_errors() {
+        assert_eq!(
+            MemMap::from_str("0x0 0x1000"),
+            Err(MemMapError::InvalidLine(1))
+        );
+        assert_eq!(
+            MemMap::from_str("0x0 0x1000 ram\n0x0 0x1000g ram"),
+            Err(MemMapError::InvalidAddress(2))
+        );
+        assert_eq!(
+            MemMap::from_str("0x1000 0x0 ram"),
+            Err(MemMapError::InvalidRange(1))
+        );
+        assert_eq!(
+            MemMap::from_str("0x0 0x1000 rom"),
+            Err(MemMapError::UnknownType(1))
+        );
+    }
+}
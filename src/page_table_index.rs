@@ -26,28 +26,33 @@ SOFTWARE.
 //! page table and given paging characteristics.
 
 use crate::addr_width::AddrWidth;
-use crate::cli::VirtualAddress;
+use crate::paging_info::PagingImplInfo;
+use crate::VirtualAddress;
+use serde::Serialize;
+use std::error::Error;
 
 /// Creates a bitmask with only ones from a number that describes how many ones
 /// there should be `(0..=64)`. The ones are filled in from the right side.
-pub fn one_bitmask_of_length(mut val: u64) -> u64 {
+///
+/// A length of 64 results in all ones. Shifting `1` by 64 would overflow, so
+/// the mask is created by shifting all ones to the right instead.
+pub const fn one_bitmask_of_length(val: u64) -> u64 {
     assert!(val <= 64);
-    let mut bitmask = 0;
-    while val > 0 {
-        bitmask <<= 1;
-        bitmask |= 1;
-        val -= 1;
+    if val == 0 {
+        0
+    } else {
+        u64::MAX >> (64 - val)
     }
-    bitmask
 }
 
 /// Contains the page table lookup meta info for a virtual address and a certain
 /// level. Meta means that only information for the lookup itself are included
 /// but not the lookup itself.
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 pub struct PageTableLookupMetaInfo {
     /// Virtual address used to get the lookup info.
     #[allow(unused)]
+    #[serde(skip)]
     pub v_addr: VirtualAddress,
     /// Used level for the lookup.
     pub level: u64,
@@ -60,7 +65,32 @@ pub struct PageTableLookupMetaInfo {
     pub shift: u64,
     /// Like `v_addr` but all bits irrelevant for the given level are zeroes.
     #[allow(unused)]
-    pub relevant_part_of_addr: u64,
+    #[serde(rename = "relevant_bits")]
+    pub relevant_part_of_addr: u128,
+}
+
+impl PageTableLookupMetaInfo {
+    /// Returns the `(row, column)` of the entry in a hex dump of the page table
+    /// with `columns` entries per row. Both start at zero.
+    pub const fn dump_position(&self, columns: u64) -> (u64, u64) {
+        assert!(columns > 0);
+        (self.index / columns, self.index % columns)
+    }
+
+    /// Returns the offset in bytes of the entry into the page table, i.e.,
+    /// `index * entry_size`. This is a `u128`, as the offset doesn't fit into
+    /// a `u64` for tables with 64 index bits.
+    pub const fn entry_offset(&self, entry_size: u64) -> u128 {
+        self.index as u128 * entry_size as u128
+    }
+
+    /// Returns the number of the cache line within the page table that holds
+    /// the entry, i.e., `index * entry_size / cache_line_size`. Entries on the
+    /// same cache line are prone to false sharing.
+    pub const fn cache_line(&self, entry_size: u64, cache_line_size: u64) -> u128 {
+        assert!(cache_line_size > 0);
+        self.entry_offset(entry_size) / cache_line_size as u128
+    }
 }
 
 /// Calculates the index into the page table for the given level and the
@@ -68,9 +98,9 @@ pub struct PageTableLookupMetaInfo {
 ///
 /// # Parameters
 /// - `index_bits` - number of how many bits index into each page table (e.g.
-///                  10 on x86 or 9 on x86 with PAE or `x86_64`)
+///   10 on x86 or 9 on x86 with PAE or `x86_64`)
 /// - `page_offset_bits` - number of how many bits index into the page (e.g. 12
-///                        on `x86` and `x86_64`, i.e., 4096 bytes per page)
+///   on `x86` and `x86_64`, i.e., 4096 bytes per page)
 /// - `addr` - Virtual Address used to look-up the page table.
 /// - `level` - Level of the page table. Must be bigger than zero!
 /// - `addr_width` - Width of the address. See [`AddrWidth`].
@@ -87,12 +117,7 @@ pub fn calculate_page_table_index(
     assert!(level > 0);
 
     let v_addr = v_addr.into();
-    let addr = u64::from(v_addr);
-    let addr = if addr_width == AddrWidth::Bits32 {
-        addr & u32::MAX as u64
-    } else {
-        addr
-    };
+    let addr = addr_width.truncate(v_addr.as_u128());
 
     // Shift the bits that index into the page table to the right.
     // To do that, we calc the number of bits to shift the virtual address.
@@ -100,9 +125,9 @@ pub fn calculate_page_table_index(
 
     let shifted_addr = addr >> shift;
 
-    let bitmask = one_bitmask_of_length(index_bits);
+    let bitmask = u128::from(one_bitmask_of_length(index_bits));
 
-    let index = shifted_addr & bitmask;
+    let index = (shifted_addr & bitmask) as u64;
     let relevant_part_of_addr = addr & (bitmask << shift);
 
     PageTableLookupMetaInfo {
@@ -114,9 +139,74 @@ pub fn calculate_page_table_index(
     }
 }
 
+/// Describes why page table indices can't be composed to a virtual address.
+/// See [`compose_virtual_address`].
+#[derive(Copy, Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum ComposeError {
+    /// The number of indices doesn't match the number of levels.
+    #[display("Expected {expected} indices, one per level, but got {actual}.")]
+    WrongIndexCount {
+        /// Number of levels of the paging implementation.
+        expected: u64,
+        /// Number of given indices.
+        actual: u64,
+    },
+    /// An index doesn't fit into the page table of its level.
+    #[display(
+        "The index {index:#x} exceeds the {entries} entries of the level {level} page table."
+    )]
+    IndexOutOfRange {
+        /// Level of the page table.
+        level: u64,
+        /// The invalid index.
+        index: u64,
+        /// Number of entries of the page table.
+        entries: u64,
+    },
+}
+
+impl Error for ComposeError {}
+
+/// Composes the canonical virtual address from the page table indices of all
+/// levels, ordered from the top level to level 1.
+///
+/// This is the inverse of [`calculate_page_table_index`]. Each index is
+/// shifted into place and the page offset is filled with zeroes, i.e., the
+/// result is the base address of the page.
+pub fn compose_virtual_address(
+    indices: &[u64],
+    paging_info: &PagingImplInfo,
+) -> Result<VirtualAddress, ComposeError> {
+    if indices.len() as u64 != paging_info.walked_levels() {
+        return Err(ComposeError::WrongIndexCount {
+            expected: paging_info.walked_levels(),
+            actual: indices.len() as u64,
+        });
+    }
+    let mut addr = 0;
+    let levels = paging_info.leaf_level..=paging_info.levels;
+    for (&index, level) in indices.iter().zip(levels.rev()) {
+        let index_bits = paging_info.index_bits_at_level(level);
+        if index > one_bitmask_of_length(index_bits) {
+            // With 64 index bits, every index is in range, so the number of
+            // entries fits into an u64 here.
+            return Err(ComposeError::IndexOutOfRange {
+                level,
+                index,
+                entries: 1 << index_bits,
+            });
+        }
+        addr |= u128::from(index) << paging_info.bit_range(level).start();
+    }
+    Ok(VirtualAddress::from_u128(
+        paging_info.canonicalize_u128(addr),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::paging_info::impls;
 
     #[test]
     fn test_one_bitmask_of_length() {
@@ -124,9 +214,29 @@ mod tests {
         assert_eq!(one_bitmask_of_length(1), 1);
         assert_eq!(one_bitmask_of_length(2), 0b11);
         assert_eq!(one_bitmask_of_length(4), 0xf);
+        assert_eq!(one_bitmask_of_length(63), u64::MAX >> 1);
         assert_eq!(one_bitmask_of_length(64), !0);
     }
 
+    #[test]
+    fn test_compose_with_64_index_bits() {
+        // a single level with 64 index bits: the index covers bits 12..=75
+        let info = PagingImplInfo::generic(128, 1, 64, 12, 8).unwrap();
+        assert!(compose_virtual_address(&[u64::MAX], &info).is_ok());
+        let v_addr = compose_virtual_address(&[u64::MAX >> 1], &info).unwrap();
+        assert_eq!(v_addr.as_u128(), u128::from(u64::MAX >> 1) << 12);
+        let lookup = info.calc_page_table_lookup_meta_info(v_addr);
+        assert_eq!(lookup[0].index, u64::MAX >> 1);
+        assert_eq!(lookup[0].relevant_part_of_addr, v_addr.as_u128());
+
+        // the offset of the last entry doesn't fit into a u64
+        let lookup = info
+            .calc_page_table_lookup_meta_info(VirtualAddress::from_u128(0xfff_ffff_ffff_ffff_f000));
+        assert_eq!(lookup[0].index, u64::MAX);
+        assert_eq!(lookup[0].entry_offset(8), u128::from(u64::MAX) * 8);
+        assert_eq!(lookup[0].cache_line(8, 64), u128::from(u64::MAX) / 8);
+    }
+
     #[test]
     fn test_calculate_page_table_index_x86() {
         // a 32-bit address written so that it is separated by the corresponding levels
@@ -144,7 +254,7 @@ mod tests {
                 l2_index, 0b1111111111,
                 "Should be 0b1111111111 but is {l2_index:#b}",
             );
-            let expected_bits: u64 = 0b1111111111 << (10 + 12);
+            let expected_bits: u128 = 0b1111111111 << (10 + 12);
             assert_eq!(
                 l2_bits, expected_bits,
                 "Should be {l2_bits:#b} but is {expected_bits:#b}"
@@ -161,7 +271,7 @@ mod tests {
                 l1_index, 0b1010101010,
                 "Should be 0b1010101010 but is {l1_index:#b}",
             );
-            let expected_bits: u64 = 0b1010101010 << 12;
+            let expected_bits: u128 = 0b1010101010 << 12;
             assert_eq!(
                 l1_bits, expected_bits,
                 "Should be {l1_bits:#b} but is {expected_bits:#b}"
@@ -183,7 +293,7 @@ mod tests {
                 ..
             } = calculate_page_table_index(9, 12, addr, 3, AddrWidth::Bits32);
             assert_eq!(l3_index, 0b10, "Should be 0b10 but is {l3_index:#b}",);
-            let expected_bits: u64 = 0b10 << (9 * 2 + 12);
+            let expected_bits: u128 = 0b10 << (9 * 2 + 12);
             assert_eq!(
                 l3_bits, expected_bits,
                 "Should be {l3_bits:#b} but is {expected_bits:#b}"
@@ -200,7 +310,7 @@ mod tests {
                 l2_index, 0b111111111,
                 "Should be 0b111111111 but is {l2_index:#b}",
             );
-            let expected_bits: u64 = 0b111111111 << (9 + 12);
+            let expected_bits: u128 = 0b111111111 << (9 + 12);
             assert_eq!(
                 l2_bits, expected_bits,
                 "Should be {l2_bits:#b} but is {expected_bits:#b}"
@@ -217,7 +327,7 @@ mod tests {
                 l1_index, 0b010101010,
                 "Should be 0b010101010 but is {l1_index:#b}",
             );
-            let expected_bits: u64 = 0b010101010 << 12;
+            let expected_bits: u128 = 0b010101010 << 12;
             assert_eq!(
                 l1_bits, expected_bits,
                 "Should be {l1_bits:#b} but is {expected_bits:#b}"
@@ -243,7 +353,7 @@ mod tests {
                 l4_index, 0b000100000,
                 "Should be 0b000100000 but is {l4_index:#b}"
             );
-            let expected_bits: u64 = 0b000100000 << (3 * 9 + 12);
+            let expected_bits: u128 = 0b000100000 << (3 * 9 + 12);
             assert_eq!(
                 l4_bits, expected_bits,
                 "Should be {l4_bits:#b} but is {expected_bits:#b}"
@@ -260,7 +370,7 @@ mod tests {
                 l3_index, 0b000011111,
                 "Should be 0b000011111 but is {l3_index:#b}"
             );
-            let expected_bits: u64 = 0b000011111 << (2 * 9 + 12);
+            let expected_bits: u128 = 0b000011111 << (2 * 9 + 12);
             assert_eq!(
                 l3_bits, expected_bits,
                 "Should be {l3_bits:#b} but is {expected_bits:#b}"
@@ -277,7 +387,7 @@ mod tests {
                 l2_index, 0b111111111,
                 "Should be 0b111111111 but is {l2_index:#b}"
             );
-            let expected_bits: u64 = 0b111111111 << (9 + 12);
+            let expected_bits: u128 = 0b111111111 << (9 + 12);
             assert_eq!(
                 l2_bits, expected_bits,
                 "Should be {l2_bits:#b} but is {expected_bits:#b}"
@@ -294,11 +404,74 @@ mod tests {
                 l1_index, 0b010101010,
                 "Should be 0b010101010 but is {l1_index:#b}"
             );
-            let expected_bits: u64 = 0b010101010 << 12;
+            let expected_bits: u128 = 0b010101010 << 12;
             assert_eq!(
                 l1_bits, expected_bits,
                 "Should be {l1_bits:#b} but is {expected_bits:#b}"
             );
         }
     }
+
+    #[test]
+    fn test_dump_position() {
+        let info = calculate_page_table_index(9, 12, 17 << 12, 1, AddrWidth::Bits64);
+        assert_eq!(info.index, 17);
+        assert_eq!(info.dump_position(4), (4, 1));
+        assert_eq!(info.dump_position(1), (17, 0));
+    }
+
+    #[test]
+    fn test_cache_line() {
+        let info = calculate_page_table_index(9, 12, 7 << 12, 1, AddrWidth::Bits64);
+        assert_eq!(info.cache_line(8, 64), 0);
+        // the 9th entry starts at byte 64, i.e., in the second cache line
+        let info = calculate_page_table_index(9, 12, 8 << 12, 1, AddrWidth::Bits64);
+        assert_eq!(info.cache_line(8, 64), 1);
+        assert_eq!(info.cache_line(8, 128), 0);
+        assert_eq!(info.cache_line(4, 64), 0);
+    }
+
+    #[test]
+    fn test_compose_virtual_address_round_trip() {
+        for (paging_info, v_addr) in [
+            (impls::X86, 0xdead_b000),
+            (impls::X86_PAE, 0xdead_b000),
+            (impls::X86_64, 0xffff_8000_dead_b000),
+            (impls::X86_64_5LEVEL, 0xff12_3456_789a_b000),
+        ] {
+            let indices = paging_info
+                .calc_page_table_lookup_meta_info(v_addr.into())
+                .iter()
+                .rev()
+                .map(|info| info.index)
+                .collect::<Vec<_>>();
+            assert_eq!(
+                compose_virtual_address(&indices, &paging_info),
+                Ok(v_addr.into())
+            );
+        }
+    }
+
+    #[test]
+    fn test_compose_virtual_address() {
+        let v_addr = compose_virtual_address(&[0x1ff, 0x0, 0x10, 0x20], &impls::X86_64);
+        assert_eq!(v_addr, Ok(0xffff_ff80_0202_0000_u64.into()));
+        assert_eq!(
+            compose_virtual_address(&[0x200, 0, 0, 0], &impls::X86_64),
+            Err(ComposeError::IndexOutOfRange {
+                level: 4,
+                index: 0x200,
+                entries: 512
+            })
+        );
+        // the PDPT of x86 with PAE only has 4 entries
+        assert!(compose_virtual_address(&[4, 0, 0], &impls::X86_PAE).is_err());
+        assert_eq!(
+            compose_virtual_address(&[0, 0], &impls::X86_64),
+            Err(ComposeError::WrongIndexCount {
+                expected: 4,
+                actual: 2
+            })
+        );
+    }
 }
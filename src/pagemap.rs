@@ -0,0 +1,154 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module to read pagemap-derived lists of virtual pages and their physical
+//! frames, such as extracted from `/proc/<pid>/pagemap` on Linux.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::Radix;
+use std::error::Error;
+use std::fs;
+use std::str::FromStr;
+
+/// Describes errors that happened when reading or parsing a [`Pagemap`].
+#[derive(Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum PagemapError {
+    /// The pagemap file could not be read.
+    #[display("Could not read the pagemap: {_0}")]
+    Io(String),
+    /// A line doesn't consist of exactly two columns.
+    #[display("Line {_0}: expected `virtual-address frame`.")]
+    InvalidLine(usize),
+    /// The virtual address or the frame could not be parsed.
+    #[display("Line {_0}: invalid address or frame.")]
+    InvalidAddress(usize),
+}
+
+impl Error for PagemapError {}
+
+/// A single line of a [`Pagemap`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PagemapEntry {
+    /// The virtual address (of the page).
+    pub v_addr: VirtualAddress,
+    /// The physical frame number the page is mapped to.
+    pub frame: u64,
+}
+
+/// A list of virtual addresses and the physical frames they are mapped to.
+///
+/// The textual representation consists of one `virtual-address frame` line
+/// per page, where both columns are hexadecimal numbers with an optional `0x`
+/// prefix. Empty lines and lines starting with `#` are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Pagemap {
+    /// The entries in the order of the input.
+    pub entries: Vec<PagemapEntry>,
+}
+
+impl Pagemap {
+    /// Reads and parses a pagemap from the file at the given path.
+    pub fn from_file(path: &str) -> Result<Self, PagemapError> {
+        fs::read_to_string(path)
+            .map_err(|e| PagemapError::Io(e.to_string()))?
+            .parse()
+    }
+}
+
+impl FromStr for Pagemap {
+    type Err = PagemapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = vec![];
+        for (i, line) in s.lines().enumerate() {
+            let line_nr = i + 1;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let columns = line.split_whitespace().collect::<Vec<_>>();
+            let [v_addr, frame] = columns[..] else {
+                return Err(PagemapError::InvalidLine(line_nr));
+            };
+            let parse_addr = |addr: &str| {
+                VirtualAddress::parse(addr, Radix::Hex)
+                    .map_err(|_| PagemapError::InvalidAddress(line_nr))
+            };
+            entries.push(PagemapEntry {
+                v_addr: parse_addr(v_addr)?,
+                frame: parse_addr(frame)?.into(),
+            });
+        }
+        Ok(Self { entries })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pagemap_from_str() {
+        let pagemap = Pagemap::from_str(
+            "# virtual-address frame\n\
+            0x7f12_3456_7000 0x1a2b3\n\
+            \n\
+            0xdead_b000 0x42\n",
+        )
+        .unwrap();
+        assert_eq!(
+            pagemap.entries,
+            vec![
+                PagemapEntry {
+                    v_addr: 0x7f12_3456_7000.into(),
+                    frame: 0x1a2b3
+                },
+                PagemapEntry {
+                    v_addr: 0xdead_b000.into(),
+                    frame: 0x42
+                }
+            ]
+        );
+        assert_eq!(
+            Pagemap::from_str("0x1000"),
+            Err(PagemapError::InvalidLine(1))
+        );
+        assert_eq!(
+            Pagemap::from_str("0x1000 0x1\n0x2000 0xzz"),
+            Err(PagemapError::InvalidAddress(2))
+        );
+    }
+
+    #[test]
+    fn test_pagemap_from_str_bare_hex() {
+        let pagemap = Pagemap::from_str("7f1234567000 1a2b3").unwrap();
+        assert_eq!(
+            pagemap.entries,
+            vec![PagemapEntry {
+                v_addr: 0x7f12_3456_7000.into(),
+                frame: 0x1a2b3
+            }]
+        );
+    }
+}
@@ -24,10 +24,104 @@ SOFTWARE.
 //! Module for specific paging implementations.
 
 use crate::addr_width::AddrWidth;
-use crate::cli::{Architecture, VirtualAddress};
-use crate::page_table_index::{calculate_page_table_index, PageTableLookupMetaInfo};
+use crate::page_table_index::{
+    calculate_page_table_index, compose_virtual_address, one_bitmask_of_length, ComposeError,
+    PageTableLookupMetaInfo,
+};
+use crate::VirtualAddress;
+use serde::Serialize;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::RangeInclusive;
 
-#[derive(Debug)]
+/// The inclusive ranges of canonical virtual addresses of a paging
+/// implementation. See [`PagingImplInfo::canonical_bounds`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CanonicalBounds {
+    /// The lower half of the address space, which always starts at zero.
+    pub lower_half: RangeInclusive<u128>,
+    /// The higher half of the address space, if the paging implementation
+    /// translates fewer bits than the address width and requires the upper
+    /// bits to be a sign extension.
+    pub higher_half: Option<RangeInclusive<u128>>,
+}
+
+/// The half of the virtual address space that an address belongs to. See
+/// [`PagingImplInfo::address_half`].
+#[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum Half {
+    /// The lower half, which starts at zero and is typically used by user
+    /// space.
+    Lower,
+    /// The higher half, which ends at the highest address and is typically
+    /// used by the kernel.
+    Higher,
+}
+
+/// Describes why a virtual address is not canonical. See
+/// [`PagingImplInfo::check_canonical`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonCanonicalAddressError {
+    /// The bits above the translated bits, which the page table walk ignores,
+    /// such as `48..=63` on x86_64 with 4-level paging.
+    pub ignored_bits: RangeInclusive<u64>,
+    /// Mask of the ignored bits that aren't a copy of the most significant
+    /// translated bit.
+    pub invalid_bits: u128,
+}
+
+impl Display for NonCanonicalAddressError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "bits {}..{} are ignored by the page table walk and must be copies of bit {}",
+            self.ignored_bits.end(),
+            self.ignored_bits.start(),
+            self.ignored_bits.start() - 1
+        )
+    }
+}
+
+impl Error for NonCanonicalAddressError {}
+
+/// Describes why the parameters of a generic paging implementation are
+/// invalid. See [`PagingImplInfo::generic`].
+#[derive(Copy, Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum GenericPagingError {
+    /// The address width is neither 32, 64, nor 128 bits.
+    #[display("The address width must be 32, 64, or 128 bits, but is {_0}.")]
+    UnsupportedAddrWidth(u64),
+    /// There must be at least one level.
+    #[display("There must be at least one page-table level.")]
+    NoLevels,
+    /// Each page table must be indexed by at least one bit.
+    #[display("The page table index must have at least one bit.")]
+    NoIndexBits,
+    /// A page table index can't have more than 64 bits.
+    #[display("The page table index must have at most 64 bits, but has {_0}.")]
+    TooManyIndexBits(u64),
+    /// The page offset must have at least one bit.
+    #[display("The page offset must have at least one bit.")]
+    NoPageOffsetBits,
+    /// The entry size must be a power of two.
+    #[display("The page table entry size must be a power of two, but is {_0}.")]
+    InvalidEntrySize(u64),
+    /// A page can't hold a whole number of page table entries.
+    #[display("The page table entry size {_0} doesn't divide the page size.")]
+    EntrySizeExceedsPageSize(u64),
+    /// The top level doesn't get a single bit of the address, or a single
+    /// entry covers more than 2^63 bytes.
+    #[display(
+        "The levels and bits exceed the address width, or the top level starts at bit 64 or above."
+    )]
+    ExceedsAddrWidth,
+}
+
+impl Error for GenericPagingError {}
+
+/// Describes the characteristics of a paging implementation, such as x86_64
+/// with 4-level paging. See [`impls`] for the known implementations.
+#[derive(Debug, PartialEq, Eq, Serialize)]
 pub struct PagingImplInfo {
     /// Short name of the paging implementation.
     pub name: &'static str,
@@ -42,28 +136,161 @@ pub struct PagingImplInfo {
     /// value equals the number of entries per page table. This implementation
     /// relies on the fact that the amount of bits indexing a page-table do not
     /// dynamically vary in the middle of the address, which is not done by any
-    /// paging implementation luckily.
+    /// paging implementation luckily. Only the top level may differ, see
+    /// [`Self::top_level_index_bits`].
     pub page_table_index_bits: u64,
+    /// Number of bits used to index into the page table of the top level, if
+    /// it differs from [`Self::page_table_index_bits`], such as the 2 bits of
+    /// the 4-entry PDPT of x86 with PAE.
+    pub top_level_index_bits: Option<u64>,
     /// Size of a page table entry in bytes.
     pub page_table_entry_size: u64,
     /// Number of page-table levels.
     pub levels: u64,
+    /// Names of the page tables of each level, as used by the vendor's
+    /// manual, starting with level 1. For example, `PT`, `PD`, `PDPT`, and
+    /// `PML4` on x86_64.
+    pub level_names: &'static [&'static str],
+    /// Levels at which a page-table entry can map a huge page directly instead
+    /// of referencing a next-level page table.
+    pub huge_page_levels: &'static [u64],
+    /// Level at which the page table walk ends, i.e., whose entries map the
+    /// pages of [`Self::page_offset_bits`]. This is level 1, unless
+    /// [`Self::with_page_size`] selected a huge page, such as level 2 for 2 MiB
+    /// pages on x86_64. The levels below are part of the page offset.
+    pub leaf_level: u64,
 }
 
 impl PagingImplInfo {
-    /// Const constructor for [`PagingImplInfo`] from [`Architecture`]. Returns one
-    /// of the constants of the [`impls`] module.
-    pub const fn from_arch(arch: Architecture) -> Self {
-        match arch {
-            Architecture::X86 { pae: false, .. } => impls::X86,
-            Architecture::X86 { pae: true, .. } => impls::X86_PAE,
-            Architecture::X86_64 {
-                five_level: false, ..
-            } => impls::X86_64,
-            Architecture::X86_64 {
-                five_level: true, ..
-            } => impls::X86_64_5LEVEL,
+    /// Constructs a [`PagingImplInfo`] for a generic paging implementation
+    /// from the given parameters. The address width must be 32, 64, or 128.
+    /// Like for x86 with PAE, the top level may have fewer index bits than the
+    /// other levels, but it must have at least one bit of the address.
+    ///
+    /// The memory covered by a single entry must fit into an [`u64`], see
+    /// [`Self::coverage_per_entry`]. Hence, the top level must start below bit
+    /// 64, even with 128-bit addresses. For example, 4 levels of 16 index bits
+    /// and 12 page offset bits are fine, but 5 levels are not.
+    pub fn generic(
+        addr_width: u64,
+        levels: u64,
+        index_bits: u64,
+        page_offset_bits: u64,
+        entry_size: u64,
+    ) -> Result<Self, GenericPagingError> {
+        let addr_width = match addr_width {
+            32 => AddrWidth::Bits32,
+            64 => AddrWidth::Bits64,
+            128 => AddrWidth::Bits128,
+            _ => return Err(GenericPagingError::UnsupportedAddrWidth(addr_width)),
+        };
+        if levels == 0 {
+            return Err(GenericPagingError::NoLevels);
+        }
+        if index_bits == 0 {
+            return Err(GenericPagingError::NoIndexBits);
+        }
+        if index_bits > 64 {
+            return Err(GenericPagingError::TooManyIndexBits(index_bits));
+        }
+        if page_offset_bits == 0 {
+            return Err(GenericPagingError::NoPageOffsetBits);
+        }
+        if !entry_size.is_power_of_two() {
+            return Err(GenericPagingError::InvalidEntrySize(entry_size));
         }
+        if page_offset_bits < 64 && entry_size > 1 << page_offset_bits {
+            return Err(GenericPagingError::EntrySizeExceedsPageSize(entry_size));
+        }
+        let top_level_shift = (levels - 1)
+            .checked_mul(index_bits)
+            .and_then(|bits| bits.checked_add(page_offset_bits));
+        if top_level_shift
+            .filter(|&shift| shift < u64::from(addr_width).min(64))
+            .is_none()
+        {
+            return Err(GenericPagingError::ExceedsAddrWidth);
+        }
+        Ok(Self {
+            name: "generic paging",
+            description: "Generic paging implementation with user-provided parameters.",
+            addr_width,
+            page_offset_bits,
+            page_table_index_bits: index_bits,
+            top_level_index_bits: None,
+            page_table_entry_size: entry_size,
+            levels,
+            level_names: &[],
+            huge_page_levels: &[],
+            leaf_level: 1,
+        })
+    }
+
+    /// Returns the paging implementation for pages of the given size in bytes.
+    ///
+    /// Besides the regular page size, this can be the size of a huge page
+    /// that is mapped directly by an entry at one of the
+    /// [`Self::huge_page_levels`]. The levels below are then part of the page
+    /// offset and not walked anymore, i.e., the level of the huge page
+    /// becomes the [`Self::leaf_level`]. The levels keep their numbers.
+    /// Returns `None` for an unsupported page size.
+    pub fn with_page_size(self, page_size: u64) -> Option<Self> {
+        if !page_size.is_power_of_two() {
+            return None;
+        }
+        let leaf_level =
+            self.terminal_level_for_page_size(u64::from(page_size.trailing_zeros()))?;
+        Some(Self {
+            page_offset_bits: *self.bit_range(leaf_level).start(),
+            leaf_level,
+            ..self
+        })
+    }
+
+    /// Returns the number of levels that the page table walk visits, i.e.,
+    /// from the top level down to the [`Self::leaf_level`]. This equals
+    /// [`Self::levels`], unless the walk ends at a huge page.
+    pub const fn walked_levels(&self) -> u64 {
+        self.levels - self.leaf_level + 1
+    }
+
+    /// Returns the number of bits of the offset into a page of the smallest
+    /// size, i.e., [`Self::page_offset_bits`] plus the index bits of the
+    /// levels below the [`Self::leaf_level`].
+    const fn base_page_offset_bits(&self) -> u64 {
+        self.page_offset_bits - (self.leaf_level - 1) * self.page_table_index_bits
+    }
+
+    /// Returns the lowest address bit that indexes into the page table at the
+    /// given level, such as 12 for level 1 and 21 for level 2 on x86_64.
+    const fn level_shift(&self, level: u64) -> u64 {
+        assert!(level > 0);
+        self.base_page_offset_bits() + (level - 1) * self.page_table_index_bits
+    }
+
+    /// Returns whether a page table exactly fills a page, i.e., whether
+    /// `2^page_table_index_bits * page_table_entry_size == 2^page_offset_bits`.
+    ///
+    /// This holds for all known paging implementations and is unusual
+    /// otherwise, but not invalid. Only the top level may have fewer entries,
+    /// such as the 4-entry PDPT of x86 with PAE.
+    pub const fn table_fills_page(&self) -> bool {
+        self.table_size_bytes() == 1 << self.page_offset_bits
+    }
+
+    /// Returns the number of entries of a page table, i.e., 2 to the power of
+    /// [`Self::page_table_index_bits`]. Only the top level may have fewer
+    /// entries, see [`Self::index_bits_at_level`].
+    ///
+    /// This is an [`u128`] as a table with 64 index bits has `2^64` entries.
+    pub const fn entries_per_table(&self) -> u128 {
+        1 << self.page_table_index_bits
+    }
+
+    /// Returns the size of a page table in bytes, i.e., the number of entries
+    /// times the size of an entry.
+    pub const fn table_size_bytes(&self) -> u128 {
+        (self.page_table_entry_size as u128) << self.page_table_index_bits
     }
 
     /// Calculates the [`PageTableLookupMetaInfo`] for all levels for a virtual
@@ -74,27 +301,463 @@ impl PagingImplInfo {
         &self,
         v_addr: VirtualAddress,
     ) -> Vec<PageTableLookupMetaInfo> {
-        let mut level = 0;
-        let mut level_info_vec = vec![];
-        while level < self.levels {
-            level += 1;
-            let info = calculate_page_table_index(
+        self.page_table_lookup_meta_info_iter(v_addr).collect()
+    }
+
+    /// Like [`Self::calc_page_table_lookup_meta_info`], but returns a
+    /// fixed-size array instead of a [`Vec`] to avoid the heap allocation in
+    /// hot paths. `N` must equal the number of page-table levels, such as `4`
+    /// for x86_64 with 4-level paging.
+    pub fn calc_lookup_fixed<const N: usize>(
+        &self,
+        v_addr: VirtualAddress,
+    ) -> [PageTableLookupMetaInfo; N] {
+        assert_eq!(
+            N as u64,
+            self.walked_levels(),
+            "N must equal the number of levels"
+        );
+        let mut infos = self.page_table_lookup_meta_info_iter(v_addr);
+        std::array::from_fn(|_| infos.next().unwrap())
+    }
+
+    /// Like [`Self::calc_page_table_lookup_meta_info`], but calculates the
+    /// [`PageTableLookupMetaInfo`] of each level lazily, from the
+    /// [`Self::leaf_level`] to level n, without allocating.
+    pub fn page_table_lookup_meta_info_iter(
+        &self,
+        v_addr: VirtualAddress,
+    ) -> impl DoubleEndedIterator<Item = PageTableLookupMetaInfo> + ExactSizeIterator + '_ {
+        // A `Range<usize>` is an `ExactSizeIterator`, unlike `RangeInclusive`.
+        (0..self.walked_levels() as usize).map(move |i| {
+            let level = self.leaf_level + i as u64;
+            let mut info = calculate_page_table_index(
                 self.page_table_index_bits,
                 self.page_offset_bits,
                 v_addr,
-                level,
+                i as u64 + 1,
                 self.addr_width,
             );
-            level_info_vec.push(info);
+            info.level = level;
+            // The top level may be indexed by fewer bits.
+            let bitmask = one_bitmask_of_length(self.index_bits_at_level(level));
+            info.index &= bitmask;
+            info.relevant_part_of_addr &= u128::from(bitmask) << info.shift;
+            info
+        })
+    }
+
+    /// Returns the name of the page table at the given level, such as `PML4`,
+    /// if known.
+    pub fn level_name(&self, level: u64) -> Option<&'static str> {
+        assert!(level > 0);
+        self.level_names.get(level as usize - 1).copied()
+    }
+
+    /// Returns the number of bits that effectively index into the page table
+    /// at the given level. This is usually [`Self::page_table_index_bits`],
+    /// but can be fewer for the top level, either due to
+    /// [`Self::top_level_index_bits`] or if the address width ends before.
+    pub fn index_bits_at_level(&self, level: u64) -> u64 {
+        let shift = self.level_shift(level);
+        let remaining_bits = u64::from(self.addr_width).saturating_sub(shift);
+        let index_bits = if level == self.levels {
+            self.top_level_index_bits
+                .unwrap_or(self.page_table_index_bits)
+        } else {
+            self.page_table_index_bits
+        };
+        index_bits.min(remaining_bits)
+    }
+
+    /// Returns the inclusive range of the address bits that index into the
+    /// page table at the given level, such as `39..=47` for level 4 on x86_64.
+    pub fn bit_range(&self, level: u64) -> RangeInclusive<u64> {
+        let low = self.level_shift(level);
+        low..=low + self.index_bits_at_level(level) - 1
+    }
+
+    /// Returns the inclusive range of the address bits of the page offset,
+    /// such as `0..=11` for 4 KiB pages.
+    pub const fn page_offset_bit_range(&self) -> RangeInclusive<u64> {
+        0..=self.page_offset_bits - 1
+    }
+
+    /// Returns how many of the lowest index bits of the given level a TLB
+    /// entry of the given page size treats as part of the page offset. For
+    /// example, with a 2 MiB TLB page on x86_64, all 9 bits of level 1 are
+    /// absorbed into the offset.
+    pub fn tlb_absorbed_bits(&self, level: u64, tlb_page_size: u64) -> u64 {
+        let tlb_offset_bits = u64::from(tlb_page_size.trailing_zeros());
+        let bits = self.bit_range(level);
+        tlb_offset_bits
+            .saturating_sub(*bits.start())
+            .min(bits.end() - bits.start() + 1)
+    }
+
+    /// Returns the range of virtual addresses that the page table entry, which
+    /// is reached by the given path of indices, maps. The path starts at the
+    /// top level and ends with the index of the entry itself, i.e., an entry
+    /// at level `n` requires `levels - n + 1` indices.
+    pub fn entry_virtual_range(&self, path: &[u64]) -> Result<RangeInclusive<u64>, ComposeError> {
+        let path_len = path.len() as u64;
+        if path_len == 0 || path_len > self.walked_levels() {
+            return Err(ComposeError::WrongIndexCount {
+                expected: self.walked_levels(),
+                actual: path_len,
+            });
         }
-        level_info_vec
+        let level = self.levels - path_len + 1;
+        let mut indices = path.to_vec();
+        indices.resize(self.walked_levels() as usize, 0);
+        let start = u64::from(compose_virtual_address(&indices, self)?);
+        Ok(start..=start + (self.coverage_per_entry(level) - 1))
+    }
+
+    /// Returns the inclusive start and end of the virtual addresses that use
+    /// the given index at the given level while all other index bits are
+    /// zero. The range spans the memory covered by a single entry, e.g.,
+    /// `(0x0, 0xfff)` for index 0 at level 1 on x86_64. The start is
+    /// canonicalized.
+    pub fn address_range_for_index(&self, level: u64, index: u64) -> (u64, u64) {
+        assert!((self.leaf_level..=self.levels).contains(&level));
+        assert!(index <= one_bitmask_of_length(self.index_bits_at_level(level)));
+        let start = self.canonicalize(index << self.bit_range(level).start());
+        (start, start + (self.coverage_per_entry(level) - 1))
+    }
+
+    /// Returns whether the page table entries have the x86 layout, i.e., the
+    /// present bit 0, the page size bit 7, and the physical address in the
+    /// upper bits. This holds for the x86 implementations of [`impls`], also
+    /// with a huge [`Self::leaf_level`], but not for generic ones.
+    pub fn has_x86_entries(&self) -> bool {
+        [
+            impls::X86,
+            impls::X86_PAE,
+            impls::X86_64,
+            impls::X86_64_5LEVEL,
+        ]
+        .iter()
+        .any(|info| info.name == self.name)
+    }
+
+    /// Returns the physical base address of the frame or huge page that the
+    /// raw page table entry at the given level references. The flag bits and,
+    /// for huge pages, the bits below the huge-page alignment are masked out.
+    /// Entries of 32-bit in size hold 32-bit physical addresses, bigger
+    /// entries up to 52-bit physical addresses. This is only meaningful for
+    /// entries with the x86 layout, see [`Self::has_x86_entries`].
+    pub const fn entry_frame_base(&self, entry: u64, level: u64) -> u64 {
+        let phys_addr_bits = if self.page_table_entry_size == 4 {
+            32
+        } else {
+            52
+        };
+        let phys_addr_mask = (1 << phys_addr_bits) - 1;
+        entry & phys_addr_mask & !(self.coverage_per_entry(level) - 1)
+    }
+
+    /// Returns the offset into the page, i.e., the lowest
+    /// [`Self::page_offset_bits`] bits of the address.
+    pub fn page_offset(&self, v_addr: VirtualAddress) -> u64 {
+        u64::from(v_addr) & one_bitmask_of_length(self.page_offset_bits)
+    }
+
+    /// Returns the base address of the page or frame with the given number,
+    /// i.e., the number multiplied by the page size. Returns `None` if the
+    /// address doesn't fit into the address width.
+    pub fn frame_address(&self, frame_number: u64) -> Option<u64> {
+        frame_number
+            .checked_mul(1 << self.page_offset_bits)
+            .filter(|&addr| self.addr_width.fits(addr.into()))
+    }
+
+    /// Returns the base address of the page that contains the address, i.e.,
+    /// the address with a page offset of zero. The page table indices are the
+    /// same as those of the address.
+    pub fn page_base(&self, v_addr: VirtualAddress) -> VirtualAddress {
+        VirtualAddress::from_u128(
+            v_addr.as_u128() & !u128::from(one_bitmask_of_length(self.page_offset_bits)),
+        )
+    }
+
+    /// Returns how the translated bits of an address are split, i.e., the
+    /// number of index bits of each level from the top level to level 1,
+    /// followed by the number of page offset bits. For example, `[10, 10, 12]`
+    /// for x86 and `[2, 9, 9, 12]` for x86 with PAE.
+    pub fn bit_split(&self) -> Vec<u64> {
+        (self.leaf_level..=self.levels)
+            .rev()
+            .map(|level| self.index_bits_at_level(level))
+            .chain([self.page_offset_bits])
+            .collect()
+    }
+
+    /// Returns the running sums of the relevant address bits of each level,
+    /// from the top level to level 1, followed by the final sum that includes
+    /// the page offset. The final sum equals the address limited to the
+    /// translated bits.
+    pub fn running_sums(&self, v_addr: VirtualAddress) -> Vec<u128> {
+        let mut sum = 0;
+        let mut sums = self
+            .page_table_lookup_meta_info_iter(v_addr)
+            .rev()
+            .map(|info| {
+                sum += info.relevant_part_of_addr;
+                sum
+            })
+            .collect::<Vec<_>>();
+        sums.push(sum + u128::from(self.page_offset(v_addr)));
+        sums
+    }
+
+    /// Returns the virtual page number (VPN) of the address, i.e., the address
+    /// without the page offset and limited to the translated bits. This equals
+    /// the concatenation of the page table indices of all levels.
+    pub fn packed_vpn(&self, v_addr: VirtualAddress) -> u128 {
+        let vpn_bits = self.translated_bits() - self.page_offset_bits;
+        (v_addr.as_u128() >> self.page_offset_bits) & (u128::MAX >> (128 - vpn_bits))
+    }
+
+    /// Returns the virtual address at which the page table of the given level
+    /// is accessible through a recursive self-map, i.e., a root table entry
+    /// at index `slot` that references the root table itself. The page table
+    /// is the one that the walk of `v_addr` uses at that level, so the root
+    /// table (`level == levels`) is reached by looping through the slot at
+    /// every level.
+    ///
+    /// This is the inverse of decoding a recursive address: Each loop through
+    /// the slot moves the remaining indices of `v_addr` down by one level.
+    pub fn recursive_table_address(&self, slot: u64, v_addr: VirtualAddress, level: u64) -> u64 {
+        assert!((self.leaf_level..=self.levels).contains(&level));
+        let loops = level - self.leaf_level + 1;
+        let index_bits = self.page_table_index_bits;
+        // The shift reaches 64 for a single level of 64 index bits, which
+        // moves all remaining indices out of the VPN.
+        let mut vpn = u32::try_from(loops * index_bits)
+            .ok()
+            .and_then(|shift| self.packed_vpn(v_addr).checked_shr(shift))
+            .unwrap_or(0);
+        for i in 0..loops {
+            vpn |= u128::from(slot) << ((self.walked_levels() - 1 - i) * index_bits);
+        }
+        self.canonicalize_u128(vpn << self.page_offset_bits) as u64
+    }
+
+    /// Makes the address canonical, i.e., sign-extends the most significant
+    /// translated bit into the upper bits, if not all bits of the address
+    /// width are translated.
+    pub fn canonicalize(&self, addr: u64) -> u64 {
+        self.canonicalize_u128(addr.into()) as u64
+    }
+
+    /// Like [`Self::canonicalize`], but for addresses of up to 128 bits.
+    pub fn canonicalize_u128(&self, addr: u128) -> u128 {
+        let translated_mask = u128::MAX >> (128 - self.translated_bits());
+        let addr = addr & translated_mask;
+        let addr = if addr & (1 << (self.translated_bits() - 1)) == 0 {
+            addr
+        } else {
+            addr | !translated_mask
+        };
+        self.addr_width.truncate(addr)
+    }
+
+    /// Returns whether the address is canonical, i.e., whether the bits above
+    /// the translated bits are a sign extension of the most significant
+    /// translated bit. Such as bits 48 to 63 for bit 47 on x86_64 with 4-level
+    /// paging. Non-canonical addresses fault on access.
+    pub fn is_canonical(&self, v_addr: VirtualAddress) -> bool {
+        let addr = self.addr_width.truncate(v_addr.as_u128());
+        self.canonicalize_u128(addr) == addr
+    }
+
+    /// Like [`Self::is_canonical`], but describes which of the ignored high
+    /// bits of a non-canonical address are set incorrectly.
+    pub fn check_canonical(&self, v_addr: VirtualAddress) -> Result<(), NonCanonicalAddressError> {
+        let addr = self.addr_width.truncate(v_addr.as_u128());
+        let invalid_bits = addr ^ self.canonicalize_u128(addr);
+        if invalid_bits == 0 {
+            return Ok(());
+        }
+        Err(NonCanonicalAddressError {
+            ignored_bits: self.translated_bits()..=u64::from(self.addr_width) - 1,
+            invalid_bits,
+        })
+    }
+
+    /// Returns the number of levels with a non-zero page table index, i.e.,
+    /// how many levels of the walk diverge from the all-zero path through the
+    /// page table tree.
+    pub fn depth(&self, v_addr: VirtualAddress) -> u64 {
+        self.page_table_lookup_meta_info_iter(v_addr)
+            .filter(|info| info.index != 0)
+            .count() as u64
+    }
+
+    /// Returns a bitmask of the levels with a non-zero page table index. Bit
+    /// `i` is set if level `i + 1` has a non-zero index, i.e., bit 0
+    /// corresponds to level 1. Addresses with the same mask share the shape
+    /// of their path through the page table tree.
+    pub fn levels_mask(&self, v_addr: VirtualAddress) -> u64 {
+        self.page_table_lookup_meta_info_iter(v_addr)
+            .filter(|info| info.index != 0)
+            .fold(0, |mask, info| mask | 1 << (info.level - 1))
+    }
+
+    /// Returns a stable hash of the page table indices of all levels, i.e., of
+    /// the walk without the page offset. Addresses in the same page share the
+    /// hash.
+    ///
+    /// The hash is the 64-bit FNV-1a hash of the indices from the top level to
+    /// level 1, each as 8 little-endian bytes.
+    pub fn walk_hash(&self, v_addr: VirtualAddress) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x100_0000_01b3;
+        self.page_table_lookup_meta_info_iter(v_addr)
+            .rev()
+            .flat_map(|info| info.index.to_le_bytes())
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+            })
+    }
+
+    /// Returns the level whose entries map a page of `2^page_size_bits` bytes,
+    /// i.e., where the page table walk for such a page terminates. This is
+    /// level 1 for regular pages and one of the [`Self::huge_page_levels`]
+    /// for huge pages, such as level 2 for 2 MiB pages on x86_64. Returns
+    /// `None` if the paging implementation doesn't support the page size.
+    pub fn terminal_level_for_page_size(&self, page_size_bits: u64) -> Option<u64> {
+        (self.leaf_level..=self.levels).find(|&level| {
+            *self.bit_range(level).start() == page_size_bits
+                && (level == 1 || self.huge_page_levels.contains(&level))
+        })
+    }
+
+    /// Returns the half of the address space that the given index into the
+    /// top-level page table selects, which is determined by its most
+    /// significant bit. Such as [`Half::Higher`] for the kernel range of PML4
+    /// indices 256 to 511 on x86_64, which kernel page-table isolation (KPTI)
+    /// unmaps from the page tables used by user space. Returns `None` if
+    /// there is no higher half, see [`Self::canonical_bounds`].
+    pub fn top_level_index_half(&self, index: u64) -> Option<Half> {
+        self.canonical_bounds().higher_half?;
+        let msb = self.index_bits_at_level(self.levels) - 1;
+        if (index >> msb) & 1 == 0 {
+            Some(Half::Lower)
+        } else {
+            Some(Half::Higher)
+        }
+    }
+
+    /// Returns the highest level at which the address is aligned to the size
+    /// of a huge page, i.e., the address could be the base of a huge page
+    /// mapped by an entry at that level. Returns `None` if the address isn't
+    /// aligned to any supported huge-page size.
+    pub fn largest_aligned_huge_page_level(&self, v_addr: VirtualAddress) -> Option<u64> {
+        let addr = self.addr_width.truncate(v_addr.as_u128());
+        self.huge_page_levels
+            .iter()
+            .copied()
+            .filter(|&level| addr & u128::from(self.coverage_per_entry(level) - 1) == 0)
+            .max()
+    }
+
+    /// Returns the levels whose page table index differs between the two
+    /// virtual addresses, ordered from level 1 to level n.
+    pub fn changed_levels(&self, v_addr_a: VirtualAddress, v_addr_b: VirtualAddress) -> Vec<u64> {
+        self.calc_page_table_lookup_meta_info(v_addr_a)
+            .iter()
+            .zip(self.calc_page_table_lookup_meta_info(v_addr_b).iter())
+            .filter(|(a, b)| a.index != b.index)
+            .map(|(a, _)| a.level)
+            .collect()
+    }
+
+    /// Returns the size in bytes of the memory region that is covered by a
+    /// single entry of a page table at the given level. For example, on x86_64
+    /// a level 1 entry covers 4 KiB and a level 2 entry covers 2 MiB.
+    pub const fn coverage_per_entry(&self, level: u64) -> u64 {
+        assert!(level > 0);
+        1 << self.level_shift(level)
+    }
+
+    /// Returns the number of bits of a virtual address that are translated by
+    /// the page table walk, i.e., the bits used as page table index or page
+    /// offset. This is 48 on x86_64 with 4-level paging, for example.
+    pub fn translated_bits(&self) -> u64 {
+        let top_level_index_bits = self
+            .top_level_index_bits
+            .unwrap_or(self.page_table_index_bits);
+        let bits = self.page_offset_bits
+            + (self.walked_levels() - 1) * self.page_table_index_bits
+            + top_level_index_bits;
+        bits.min(u64::from(self.addr_width))
+    }
+
+    /// Returns the number of high bits of a virtual address that are not
+    /// translated by the page table walk, i.e., the sign-extension bits of a
+    /// canonical address. This is 16 on x86_64 with 4-level paging.
+    pub fn untranslated_bits(&self) -> u64 {
+        u64::from(self.addr_width) - self.translated_bits()
+    }
+
+    /// Returns the minimal and maximal canonical virtual addresses. If not all
+    /// bits of the address are translated, the upper bits must be a sign
+    /// extension of the most significant translated bit. This splits the
+    /// address space into a lower and a higher half.
+    pub fn canonical_bounds(&self) -> CanonicalBounds {
+        let max = self.addr_width.truncate(u128::MAX);
+        let translated_bits = self.translated_bits();
+        if translated_bits == u64::from(self.addr_width) {
+            return CanonicalBounds {
+                lower_half: 0..=max,
+                higher_half: None,
+            };
+        }
+        let lower_half_max = max >> (u64::from(self.addr_width) - translated_bits + 1);
+        CanonicalBounds {
+            lower_half: 0..=lower_half_max,
+            higher_half: Some(!lower_half_max & max..=max),
+        }
+    }
+
+    /// Returns the half of the address space that the address belongs to,
+    /// i.e., whether it is above the end of the lower half of the
+    /// [`Self::canonical_bounds`]. Such as [`Half::Higher`] for addresses with
+    /// bit 47 set on x86_64 with 4-level paging. If all bits of the address
+    /// width are translated, there is no higher half.
+    pub fn address_half(&self, addr: u64) -> Half {
+        if u128::from(addr) <= *self.canonical_bounds().lower_half.end() {
+            Half::Lower
+        } else {
+            Half::Higher
+        }
+    }
+}
+
+impl Display for PagingImplInfo {
+    /// Formats the parameters concisely, such as `x86_64 paging: 4 levels, 9
+    /// index bits, 12 page offset bits, 8-byte entries`.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} levels, {} index bits, {} page offset bits, {}-byte entries",
+            self.name,
+            self.levels,
+            self.page_table_index_bits,
+            self.page_offset_bits,
+            self.page_table_entry_size
+        )
     }
 }
 
+/// Constants of [`PagingImplInfo`] for the known paging implementations.
 pub mod impls {
     use super::*;
     use std::mem::size_of;
 
+    /// x86 32-bit paging with a 2-level page table.
     pub const X86: PagingImplInfo = PagingImplInfo {
         name: "x86 32-bit paging",
         levels: 2,
@@ -106,9 +769,15 @@ pub mod impls {
         addr_width: AddrWidth::Bits32,
         page_offset_bits: 12,
         page_table_index_bits: 10,
+        top_level_index_bits: None,
         page_table_entry_size: size_of::<u32>() as u64,
+        level_names: &["PT", "PD"],
+        huge_page_levels: &[2],
+        leaf_level: 1,
     };
 
+    /// x86 32-bit paging with the Physical Address Extension (PAE) and a
+    /// 3-level page table.
     pub const X86_PAE: PagingImplInfo = PagingImplInfo {
         name: "x86 32-bit paging with PAE",
         levels: 3,
@@ -124,9 +793,14 @@ pub mod impls {
         addr_width: AddrWidth::Bits32,
         page_offset_bits: 12,
         page_table_index_bits: 9,
+        top_level_index_bits: Some(2),
         page_table_entry_size: size_of::<u64>() as u64,
+        level_names: &["PT", "PD", "PDPT"],
+        huge_page_levels: &[2],
+        leaf_level: 1,
     };
 
+    /// x86_64 paging with a 4-level page table.
     pub const X86_64: PagingImplInfo = PagingImplInfo {
         name: "x86_64 paging",
         levels: 4,
@@ -138,9 +812,14 @@ pub mod impls {
         addr_width: AddrWidth::Bits64,
         page_offset_bits: 12,
         page_table_index_bits: 9,
+        top_level_index_bits: None,
         page_table_entry_size: size_of::<u64>() as u64,
+        level_names: &["PT", "PD", "PDPT", "PML4"],
+        huge_page_levels: &[2, 3],
+        leaf_level: 1,
     };
 
+    /// x86_64 paging with a 5-level page table.
     pub const X86_64_5LEVEL: PagingImplInfo = PagingImplInfo {
         name: "x86_64 paging (5-level)",
         levels: 5,
@@ -152,8 +831,21 @@ pub mod impls {
         addr_width: AddrWidth::Bits64,
         page_offset_bits: 12,
         page_table_index_bits: 9,
+        top_level_index_bits: None,
         page_table_entry_size: size_of::<u64>() as u64,
+        level_names: &["PT", "PD", "PDPT", "PML4", "PML5"],
+        huge_page_levels: &[2, 3],
+        leaf_level: 1,
     };
+
+    /// All known paging implementations.
+    pub const ALL: &[PagingImplInfo] = &[X86, X86_PAE, X86_64, X86_64_5LEVEL];
+
+    /// Returns the known paging implementation with the given
+    /// [`PagingImplInfo::name`], such as `x86_64 paging`.
+    pub fn by_name(name: &str) -> Option<&'static PagingImplInfo> {
+        ALL.iter().find(|info| info.name == name)
+    }
 }
 
 #[cfg(test)]
@@ -217,4 +909,675 @@ mod tests {
         assert_eq!(vec[4].index, 0b011101110);
         assert_eq!(vec.len(), 5);
     }
+
+    #[test]
+    fn test_canonical_bounds() {
+        assert_eq!(
+            impls::X86_64.canonical_bounds(),
+            CanonicalBounds {
+                lower_half: 0..=0x0000_7fff_ffff_ffff,
+                higher_half: Some(0xffff_8000_0000_0000..=0xffff_ffff_ffff_ffff),
+            }
+        );
+        assert_eq!(
+            impls::X86_64_5LEVEL.canonical_bounds(),
+            CanonicalBounds {
+                lower_half: 0..=0x00ff_ffff_ffff_ffff,
+                higher_half: Some(0xff00_0000_0000_0000..=0xffff_ffff_ffff_ffff),
+            }
+        );
+        assert_eq!(
+            impls::X86_PAE.canonical_bounds(),
+            CanonicalBounds {
+                lower_half: 0..=0xffff_ffff,
+                higher_half: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_changed_levels() {
+        let addr = 0xdead_0000.into();
+        let two_mib = 0xdead_0000 + 0x20_0000;
+        assert_eq!(impls::X86_64.changed_levels(addr, addr), Vec::<u64>::new());
+        assert_eq!(impls::X86_64.changed_levels(addr, two_mib.into()), vec![2]);
+        assert_eq!(
+            impls::X86_64.changed_levels(addr, (0xdead_0000 + 0x1000).into()),
+            vec![1]
+        );
+    }
+
+    #[test]
+    fn test_packed_vpn() {
+        let addr = 0xffff_8000_dead_beef.into();
+        let vpn = impls::X86_64.packed_vpn(addr);
+        assert_eq!(vpn, 0x8_000d_eadb);
+
+        for info in impls::X86_64.calc_page_table_lookup_meta_info(addr) {
+            let shift = (info.level - 1) * impls::X86_64.page_table_index_bits;
+            assert_eq!((vpn >> shift) & 0x1ff, u128::from(info.index));
+        }
+
+        assert_eq!(impls::X86.packed_vpn(0x1_dead_beef.into()), 0xdeadb);
+
+        // the VPN of 80 bits doesn't fit into an u64
+        let info = PagingImplInfo::generic(128, 2, 40, 12, 8).unwrap();
+        let v_addr = VirtualAddress::from_u128(0x10_0000_0000_0000_1000);
+        assert_eq!(info.packed_vpn(v_addr), 0x100_0000_0000_0001);
+    }
+
+    #[test]
+    fn test_level_names() {
+        assert_eq!(impls::X86_PAE.level_name(3), Some("PDPT"));
+        assert_eq!(impls::X86_PAE.index_bits_at_level(3), 2);
+        assert_eq!(impls::X86_PAE.index_bits_at_level(2), 9);
+        assert_eq!(impls::X86_64.level_name(4), Some("PML4"));
+        assert_eq!(impls::X86_64.level_name(1), Some("PT"));
+        assert_eq!(impls::X86_64.index_bits_at_level(4), 9);
+        assert_eq!(impls::X86_64_5LEVEL.level_name(5), Some("PML5"));
+        assert_eq!(impls::X86.level_name(2), Some("PD"));
+        assert_eq!(impls::X86.level_name(3), None);
+    }
+
+    #[test]
+    fn test_compare_x86_with_pae() {
+        let addr = 0xdead_beef.into();
+        let x86 = impls::X86.calc_page_table_lookup_meta_info(addr);
+        let x86_pae = impls::X86_PAE.calc_page_table_lookup_meta_info(addr);
+        assert_eq!(x86.len(), 2);
+        assert_eq!(x86_pae.len(), 3);
+        assert_eq!(impls::X86.bit_split(), vec![10, 10, 12]);
+        assert_eq!(impls::X86_PAE.bit_split(), vec![2, 9, 9, 12]);
+        assert_eq!(impls::X86.page_offset(addr), 0xeef);
+        assert_eq!(impls::X86_PAE.page_offset(addr), 0xeef);
+        assert_ne!(x86[0].index, x86_pae[0].index);
+    }
+
+    #[test]
+    fn test_largest_aligned_huge_page_level() {
+        let one_gib = 0x4000_0000;
+        let two_mib = 0x20_0000;
+        assert_eq!(
+            impls::X86_64.largest_aligned_huge_page_level((3 * one_gib).into()),
+            Some(3)
+        );
+        assert_eq!(
+            impls::X86_64.largest_aligned_huge_page_level((3 * two_mib).into()),
+            Some(2)
+        );
+        assert_eq!(
+            impls::X86_64.largest_aligned_huge_page_level(0x1234.into()),
+            None
+        );
+        assert_eq!(
+            impls::X86_64.largest_aligned_huge_page_level(0x1000.into()),
+            None
+        );
+        assert_eq!(
+            impls::X86.largest_aligned_huge_page_level(0x1_0040_0000.into()),
+            Some(2)
+        );
+    }
+
+    #[test]
+    fn test_running_sums() {
+        let sums = impls::X86_64.running_sums(0xffff_8000_dead_beef.into());
+        assert_eq!(
+            sums,
+            vec![
+                0x8000_0000_0000,
+                0x8000_c000_0000,
+                0x8000_dea0_0000,
+                0x8000_dead_b000,
+                0x8000_dead_beef
+            ]
+        );
+        let sums = impls::X86.running_sums(0x1_dead_beef.into());
+        assert_eq!(sums.last(), Some(&0xdead_beef));
+    }
+
+    #[test]
+    fn test_recursive_table_address() {
+        // well-known self-map addresses of the root table on x86_64
+        assert_eq!(
+            impls::X86_64.recursive_table_address(510, 0.into(), 4),
+            0xffff_ff7f_bfdf_e000
+        );
+        assert_eq!(
+            impls::X86_64.recursive_table_address(511, 0.into(), 4),
+            0xffff_ffff_ffff_f000
+        );
+        // the level 1 table (PT) that maps 0xdeadbeef: indices 510/0/3/245
+        assert_eq!(
+            impls::X86_64.recursive_table_address(510, 0xdead_beef.into(), 1),
+            0xffff_ff00_006f_5000
+        );
+        // x86: the page directory at slot 1023 is mapped at the last page
+        assert_eq!(
+            impls::X86.recursive_table_address(1023, 0.into(), 2),
+            0xffff_f000
+        );
+    }
+
+    #[test]
+    fn test_recursive_table_address_with_64_index_bits() {
+        // looping once through the slot shifts the VPN by all 64 bits
+        let info = PagingImplInfo::generic(128, 1, 64, 12, 8).unwrap();
+        assert_eq!(
+            info.recursive_table_address(3, 0xdead_beef.into(), 1),
+            0x3000
+        );
+        assert_eq!(info.address_range_for_index(1, u64::MAX).0, u64::MAX << 12);
+    }
+
+    #[test]
+    fn test_walk_hash() {
+        let hash = impls::X86_64.walk_hash(0xdead_b000.into());
+        assert_eq!(hash, impls::X86_64.walk_hash(0xdead_beef.into()));
+        assert_ne!(hash, impls::X86_64.walk_hash(0xdead_c000.into()));
+        // FNV-1a of zero indices only
+        assert_eq!(impls::X86.walk_hash(0.into()), 0x8820_1fb9_60ff_6465);
+    }
+
+    #[test]
+    fn test_walk_of_directmap_address() {
+        // physical address 0x1000 in the direct map of Linux on x86_64
+        let v_addr = impls::X86_64
+            .addr_width
+            .checked_add(0xffff_8880_0000_0000, 0x1000)
+            .unwrap();
+        assert_eq!(v_addr, 0xffff_8880_0000_1000);
+        let indices = impls::X86_64
+            .calc_page_table_lookup_meta_info(v_addr.into())
+            .iter()
+            .map(|info| info.index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, [1, 0, 0, 273]);
+    }
+
+    #[test]
+    fn test_depth() {
+        assert_eq!(impls::X86_64.depth(0.into()), 0);
+        assert_eq!(impls::X86_64.depth(0xfff.into()), 0);
+        assert_eq!(impls::X86_64.depth(0x1000.into()), 1);
+        assert_eq!(impls::X86_64.depth(0xdead_beef.into()), 3);
+    }
+
+    #[test]
+    fn test_is_canonical() {
+        let info = impls::X86_64;
+        assert!(info.is_canonical(0x7fff_ffff_ffff.into()));
+        assert!(info.is_canonical(0xffff_8000_0000_0000.into()));
+        assert!(!info.is_canonical(0x8000_0000_0000.into()));
+        assert!(!info.is_canonical(0x0001_0000_0000_0000.into()));
+
+        let info = impls::X86_64_5LEVEL;
+        assert!(info.is_canonical(0x8000_0000_0000.into()));
+        assert!(info.is_canonical(0xff00_0000_0000_0000.into()));
+        assert!(!info.is_canonical(0x0100_0000_0000_0000.into()));
+        assert!(!info.is_canonical(0xfe00_0000_0000_0000.into()));
+
+        assert!(impls::X86.is_canonical(0xffff_ffff.into()));
+    }
+
+    #[test]
+    fn test_coverage_per_entry() {
+        let info = impls::X86_64;
+        assert_eq!(info.coverage_per_entry(1), 4 * 1024);
+        assert_eq!(info.coverage_per_entry(2), 2 * 1024 * 1024);
+        assert_eq!(info.coverage_per_entry(3), 1024 * 1024 * 1024);
+        assert_eq!(info.coverage_per_entry(4), 512 * 1024 * 1024 * 1024);
+        assert_eq!(impls::X86.coverage_per_entry(2), 4 * 1024 * 1024);
+    }
+
+    #[test]
+    fn test_untranslated_bits() {
+        assert_eq!(impls::X86_64.untranslated_bits(), 16);
+        assert_eq!(impls::X86_64_5LEVEL.untranslated_bits(), 7);
+        assert_eq!(impls::X86.untranslated_bits(), 0);
+        assert_eq!(impls::X86_PAE.untranslated_bits(), 0);
+    }
+
+    #[test]
+    fn test_display() {
+        let display = impls::X86_64.to_string();
+        assert!(display.contains("x86_64 paging"));
+        assert!(display.contains("4 levels"));
+        assert_eq!(
+            impls::X86.to_string(),
+            "x86 32-bit paging: 2 levels, 10 index bits, 12 page offset bits, 4-byte entries"
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_value(impls::X86_64).unwrap();
+        assert_eq!(json["name"], "x86_64 paging");
+        assert_eq!(json["addr_width"], 64);
+        assert_eq!(json["levels"], 4);
+        assert_eq!(json["huge_page_levels"], serde_json::json!([2, 3]));
+    }
+
+    #[test]
+    fn test_levels_mask() {
+        assert_eq!(impls::X86_64.levels_mask(0xfff.into()), 0);
+        assert_eq!(impls::X86_64.levels_mask(0x4000_1000.into()), 0b101);
+        assert_eq!(impls::X86_64.levels_mask(0xdead_beef.into()), 0b111);
+        assert_eq!(impls::X86.levels_mask(0xdead_beef.into()), 0b11);
+    }
+
+    #[test]
+    fn test_generic_matches_x86_64() {
+        let generic = PagingImplInfo::generic(64, 4, 9, 12, 8).unwrap();
+        let v_addr = 0xdead_beef.into();
+        let indices = |info: &PagingImplInfo| {
+            info.calc_page_table_lookup_meta_info(v_addr)
+                .iter()
+                .map(|info| (info.level, info.index))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(indices(&generic), indices(&impls::X86_64));
+        assert_eq!(generic.canonical_bounds(), impls::X86_64.canonical_bounds());
+    }
+
+    #[test]
+    fn test_generic_128_bit() {
+        let generic = PagingImplInfo::generic(128, 4, 16, 12, 8).unwrap();
+        let v_addr = VirtualAddress::from_u128(1 << 64 | 0x1234_5000);
+        let indices = generic
+            .calc_page_table_lookup_meta_info(v_addr)
+            .iter()
+            .map(|info| info.index)
+            .collect::<Vec<_>>();
+        assert_eq!(indices, [0x2345, 0x1, 0x0, 0x10]);
+        assert_eq!(
+            compose_virtual_address(&[0x10, 0x0, 0x1, 0x2345], &generic),
+            Ok(v_addr)
+        );
+        assert!(generic.is_canonical(v_addr));
+        assert!(!generic.is_canonical(VirtualAddress::from_u128(1 << 100)));
+        assert_eq!(
+            generic.canonical_bounds().higher_half,
+            Some(!((1 << 75) - 1)..=u128::MAX)
+        );
+        assert_eq!(
+            PagingImplInfo::generic(128, 5, 16, 12, 8).unwrap_err(),
+            GenericPagingError::ExceedsAddrWidth
+        );
+    }
+
+    #[test]
+    fn test_generic_validation() {
+        assert_eq!(
+            PagingImplInfo::generic(48, 4, 9, 12, 8).unwrap_err(),
+            GenericPagingError::UnsupportedAddrWidth(48)
+        );
+        assert_eq!(
+            PagingImplInfo::generic(64, 0, 9, 12, 8).unwrap_err(),
+            GenericPagingError::NoLevels
+        );
+        assert_eq!(
+            PagingImplInfo::generic(64, 4, 9, 12, 6).unwrap_err(),
+            GenericPagingError::InvalidEntrySize(6)
+        );
+        assert_eq!(
+            PagingImplInfo::generic(32, 4, 9, 12, 8).unwrap_err(),
+            GenericPagingError::ExceedsAddrWidth
+        );
+        assert_eq!(
+            PagingImplInfo::generic(64, 1, 100, 12, 8).unwrap_err(),
+            GenericPagingError::TooManyIndexBits(100)
+        );
+        assert_eq!(
+            PagingImplInfo::generic(128, 1, 100, 12, 8).unwrap_err(),
+            GenericPagingError::TooManyIndexBits(100)
+        );
+        assert!(PagingImplInfo::generic(128, 1, 64, 12, 8).is_ok());
+        // the 2-bit top level of x86 with PAE is fine
+        assert!(PagingImplInfo::generic(32, 3, 9, 12, 8).is_ok());
+        // 16-byte entries don't fit into 8-byte pages
+        assert_eq!(
+            PagingImplInfo::generic(64, 4, 1, 3, 16).unwrap_err(),
+            GenericPagingError::EntrySizeExceedsPageSize(16)
+        );
+    }
+
+    #[test]
+    fn test_table_fills_page() {
+        assert!(impls::X86.table_fills_page());
+        assert!(impls::X86_PAE.table_fills_page());
+        assert!(impls::X86_64.table_fills_page());
+        assert!(impls::X86_64_5LEVEL.table_fills_page());
+        // valid, but a table of 256 entries only fills half of the page
+        let info = PagingImplInfo::generic(64, 4, 8, 12, 8).unwrap();
+        assert!(!info.table_fills_page());
+    }
+
+    #[test]
+    fn test_calc_lookup_fixed() {
+        for v_addr in [0x0, 0xdead_beef, 0xffff_8000_1337_1000] {
+            let v_addr = VirtualAddress::from(v_addr);
+            let fixed = impls::X86_64.calc_lookup_fixed::<4>(v_addr);
+            assert_eq!(
+                fixed.to_vec(),
+                impls::X86_64.calc_page_table_lookup_meta_info(v_addr)
+            );
+        }
+        let fixed = impls::X86.calc_lookup_fixed::<2>(0xdead_beef.into());
+        assert_eq!(fixed[1].index, 890);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_calc_lookup_fixed_wrong_levels() {
+        let _ = impls::X86_64.calc_lookup_fixed::<5>(0xdead_beef.into());
+    }
+
+    #[test]
+    fn test_page_table_lookup_meta_info_iter() {
+        for info in impls::ALL {
+            let v_addr = 0xffff_8000_dead_beef.into();
+            let iter = info.page_table_lookup_meta_info_iter(v_addr);
+            assert_eq!(iter.len(), info.levels as usize);
+            assert_eq!(
+                iter.collect::<Vec<_>>(),
+                info.calc_page_table_lookup_meta_info(v_addr)
+            );
+        }
+    }
+
+    #[test]
+    fn test_top_level_index_bits() {
+        let info = &impls::X86_PAE;
+        assert_eq!(info.index_bits_at_level(3), 2);
+        assert_eq!(info.bit_range(3), 30..=31);
+        assert_eq!(info.translated_bits(), 32);
+        let top = info.calc_page_table_lookup_meta_info(0xdead_beef.into())[2];
+        assert_eq!(top.shift, 30);
+        assert_eq!(top.index, 3);
+
+        // Only the lowest bit of the top level indexes into the table.
+        let info = PagingImplInfo {
+            top_level_index_bits: Some(1),
+            ..impls::X86_64
+        };
+        assert_eq!(info.translated_bits(), 40);
+        let top = info.calc_page_table_lookup_meta_info(0xff80_0000_0000.into())[3];
+        assert_eq!(top.shift, 39);
+        assert_eq!(top.index, 1);
+        assert_eq!(top.relevant_part_of_addr, 1 << 39);
+    }
+
+    #[test]
+    fn test_address_half() {
+        let info = impls::X86_64;
+        assert_eq!(info.address_half(0x1000), Half::Lower);
+        assert_eq!(info.address_half(0x7fff_ffff_ffff), Half::Lower);
+        assert_eq!(info.address_half(0xffff_8000_0000_0000), Half::Higher);
+        assert_eq!(info.address_half(u64::MAX), Half::Higher);
+        assert_eq!(
+            impls::X86_64_5LEVEL.address_half(0xffff_8000_0000_0000),
+            Half::Higher
+        );
+        assert_eq!(
+            impls::X86_64_5LEVEL.address_half(0x7fff_ffff_ffff),
+            Half::Lower
+        );
+        // no sign extension on x86
+        assert_eq!(impls::X86.address_half(0xffff_f000), Half::Lower);
+    }
+
+    #[test]
+    fn test_top_level_index_half() {
+        let info = impls::X86_64;
+        assert_eq!(info.top_level_index_half(0), Some(Half::Lower));
+        assert_eq!(info.top_level_index_half(255), Some(Half::Lower));
+        assert_eq!(info.top_level_index_half(256), Some(Half::Higher));
+        assert_eq!(info.top_level_index_half(511), Some(Half::Higher));
+        let top = info.calc_page_table_lookup_meta_info(0xffff_8000_0000_0000.into())[3];
+        assert_eq!(info.top_level_index_half(top.index), Some(Half::Higher));
+        assert_eq!(
+            impls::X86_64_5LEVEL.top_level_index_half(256),
+            Some(Half::Higher)
+        );
+        assert_eq!(impls::X86.top_level_index_half(1023), None);
+    }
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(impls::by_name("x86_64 paging"), Some(&impls::X86_64));
+        assert_eq!(
+            impls::by_name("x86 32-bit paging with PAE"),
+            Some(&impls::X86_PAE)
+        );
+        for info in impls::ALL {
+            assert_eq!(impls::by_name(info.name), Some(info));
+        }
+        assert_eq!(impls::by_name("x86_64"), None);
+        assert_eq!(impls::by_name("generic paging"), None);
+    }
+
+    #[test]
+    fn test_check_canonical() {
+        let info = impls::X86_64;
+        assert_eq!(info.check_canonical(0xffff_8000_0000_0000.into()), Ok(()));
+        assert_eq!(info.check_canonical(0x7fff_ffff_ffff.into()), Ok(()));
+        let error = info
+            .check_canonical(0x0001_0000_dead_beef.into())
+            .unwrap_err();
+        assert_eq!(error.ignored_bits, 48..=63);
+        assert_eq!(error.invalid_bits, 1 << 48);
+        assert_eq!(
+            error.to_string(),
+            "bits 63..48 are ignored by the page table walk and must be copies of bit 47"
+        );
+        // bit 47 is set, so bits 48 to 63 must be set, too
+        let error = info
+            .check_canonical(0x0fff_8000_0000_0000.into())
+            .unwrap_err();
+        assert_eq!(error.invalid_bits, 0xf000_0000_0000_0000);
+
+        // 5-level paging translates bits 48 to 56
+        let info = impls::X86_64_5LEVEL;
+        assert_eq!(info.check_canonical(0x0001_0000_dead_beef.into()), Ok(()));
+        let error = info
+            .check_canonical(0x1000_0000_dead_beef.into())
+            .unwrap_err();
+        assert_eq!(error.ignored_bits, 57..=63);
+        assert_eq!(error.invalid_bits, 1 << 60);
+    }
+
+    #[test]
+    fn test_table_size() {
+        assert_eq!(impls::X86_64.entries_per_table(), 512);
+        assert_eq!(impls::X86_64.table_size_bytes(), 4096);
+        assert_eq!(impls::X86.entries_per_table(), 1024);
+        let info = PagingImplInfo::generic(128, 1, 64, 12, 8).unwrap();
+        assert_eq!(info.entries_per_table(), 1 << 64);
+        assert_eq!(info.table_size_bytes(), 8 << 64);
+        assert_eq!(impls::X86.table_size_bytes(), 4096);
+    }
+
+    #[test]
+    fn test_bit_range() {
+        assert_eq!(impls::X86_64.bit_range(1), 12..=20);
+        assert_eq!(impls::X86_64.bit_range(4), 39..=47);
+        assert_eq!(impls::X86.bit_range(2), 22..=31);
+        // the PDPT of x86 with PAE only has 4 entries
+        assert_eq!(impls::X86_PAE.bit_range(3), 30..=31);
+        assert_eq!(impls::X86_64.page_offset_bit_range(), 0..=11);
+    }
+
+    #[test]
+    fn test_tlb_absorbed_bits() {
+        let two_mib = 2 * 1024 * 1024;
+        assert_eq!(impls::X86_64.tlb_absorbed_bits(1, two_mib), 9);
+        assert_eq!(impls::X86_64.tlb_absorbed_bits(2, two_mib), 0);
+        assert_eq!(impls::X86_64.tlb_absorbed_bits(1, 4096), 0);
+        assert_eq!(impls::X86_64.tlb_absorbed_bits(1, 0x4000), 2);
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(
+            impls::X86_64.canonicalize(0x8000_0000_0000),
+            0xffff_8000_0000_0000
+        );
+        assert_eq!(
+            impls::X86_64.canonicalize(0x7fff_ffff_ffff),
+            0x7fff_ffff_ffff
+        );
+        assert_eq!(
+            impls::X86_64_5LEVEL.canonicalize(0x0100_0000_0000_0000),
+            0xff00_0000_0000_0000
+        );
+        assert_eq!(impls::X86.canonicalize(0x1_dead_beef), 0xdead_beef);
+    }
+
+    #[test]
+    fn test_leaf_entry_maps_one_page() {
+        let range = impls::X86_64.entry_virtual_range(&[0, 3, 245, 219]);
+        assert_eq!(range, Ok(0xdead_b000..=0xdead_bfff));
+        // present, writable, and the NX bit set
+        let pte = 0x8000_0000_1234_5003;
+        assert_eq!(impls::X86_64.entry_frame_base(pte, 1), 0x1234_5000);
+    }
+
+    #[test]
+    fn test_address_range_for_index() {
+        assert_eq!(impls::X86_64.address_range_for_index(1, 0), (0x0, 0xfff));
+        assert_eq!(
+            impls::X86_64.address_range_for_index(1, 1),
+            (0x1000, 0x1fff)
+        );
+        assert_eq!(
+            impls::X86_64.address_range_for_index(2, 3),
+            (0x60_0000, 0x7f_ffff)
+        );
+        assert_eq!(
+            impls::X86_64.address_range_for_index(4, 256),
+            (0xffff_8000_0000_0000, 0xffff_807f_ffff_ffff)
+        );
+        assert_eq!(
+            impls::X86_PAE.address_range_for_index(3, 3),
+            (0xc000_0000, 0xffff_ffff)
+        );
+    }
+
+    #[test]
+    fn test_huge_page_entry() {
+        // a 2 MiB page at level 2 in the higher half
+        let range = impls::X86_64.entry_virtual_range(&[256, 0, 1]);
+        assert_eq!(range, Ok(0xffff_8000_0020_0000..=0xffff_8000_003f_ffff));
+        // the PAT bit (12) of the huge page is not part of the address
+        assert_eq!(impls::X86_64.entry_frame_base(0x4000_1083, 2), 0x4000_0000);
+        assert_eq!(impls::X86.entry_frame_base(0xdead_b067, 1), 0xdead_b000);
+    }
+
+    #[test]
+    fn test_has_x86_entries() {
+        assert!(impls::ALL.iter().all(PagingImplInfo::has_x86_entries));
+        let info = impls::X86_64.with_page_size(2 * 1024 * 1024).unwrap();
+        assert!(info.has_x86_entries());
+        let info = PagingImplInfo::generic(64, 4, 9, 12, 8).unwrap();
+        assert!(!info.has_x86_entries());
+        assert!(impls::X86_64.entry_virtual_range(&[]).is_err());
+    }
+
+    #[test]
+    fn test_terminal_level_for_page_size() {
+        let info = impls::X86_64;
+        assert_eq!(info.terminal_level_for_page_size(12), Some(1));
+        assert_eq!(info.terminal_level_for_page_size(21), Some(2));
+        assert_eq!(info.terminal_level_for_page_size(30), Some(3));
+        // no 512 GiB pages and no 8 KiB pages
+        assert_eq!(info.terminal_level_for_page_size(39), None);
+        assert_eq!(info.terminal_level_for_page_size(13), None);
+        // x86 without PAE only has 4 MiB huge pages
+        assert_eq!(impls::X86.terminal_level_for_page_size(22), Some(2));
+        assert_eq!(impls::X86.terminal_level_for_page_size(21), None);
+    }
+
+    #[test]
+    fn test_with_page_size() {
+        let info = impls::X86_64.with_page_size(4096).unwrap();
+        assert_eq!(info.page_offset_bits, 12);
+        assert_eq!(info.levels, 4);
+
+        let info = impls::X86_64.with_page_size(2 * 1024 * 1024).unwrap();
+        assert_eq!(info.page_offset_bits, 21);
+        assert_eq!(info.levels, 4);
+        assert_eq!(info.leaf_level, 2);
+        assert_eq!(info.walked_levels(), 3);
+        assert_eq!(info.huge_page_levels, &[2, 3]);
+        assert_eq!(info.level_name(2), Some("PD"));
+        assert_eq!(info.bit_range(2), 21..=29);
+        assert_eq!(info.coverage_per_entry(2), 2 * 1024 * 1024);
+        assert_eq!(info.translated_bits(), 48);
+        let v_addr = VirtualAddress::from(0xffff_8000_0040_1234_u64);
+        let levels = info.calc_page_table_lookup_meta_info(v_addr);
+        assert_eq!(
+            levels
+                .iter()
+                .map(|l| (l.level, l.index))
+                .collect::<Vec<_>>(),
+            [(2, 2), (3, 0), (4, 256)]
+        );
+        assert_eq!(info.page_offset(v_addr), 0x1234);
+        let indices = [256, 0, 2];
+        assert_eq!(
+            compose_virtual_address(&indices, &info),
+            Ok(VirtualAddress::from(0xffff_8000_0040_0000_u64))
+        );
+
+        let info = impls::X86_64_5LEVEL
+            .with_page_size(1024 * 1024 * 1024)
+            .unwrap();
+        assert_eq!(info.page_offset_bits, 30);
+        assert_eq!(info.levels, 5);
+        assert_eq!(info.leaf_level, 3);
+        assert_eq!(info.level_name(3), Some("PDPT"));
+        assert_eq!(info.translated_bits(), 57);
+
+        assert!(impls::X86_64.with_page_size(8192).is_none());
+        assert!(impls::X86.with_page_size(1024 * 1024 * 1024).is_none());
+    }
+
+    #[test]
+    fn test_frame_address() {
+        let info = impls::X86_64;
+        let indices = |v_addr: VirtualAddress| {
+            info.calc_page_table_lookup_meta_info(v_addr)
+                .iter()
+                .map(|info| info.index)
+                .collect::<Vec<_>>()
+        };
+        let addr = info.frame_address(1).unwrap();
+        assert_eq!(addr, 0x1000);
+        assert_eq!(indices(addr.into()), indices(0x1000.into()));
+        assert_eq!(info.page_offset(addr.into()), 0);
+
+        assert_eq!(impls::X86.frame_address(0xf_ffff), Some(0xffff_f000));
+        assert_eq!(impls::X86.frame_address(0x10_0000), None);
+        assert_eq!(info.frame_address(1 << 52), None);
+    }
+
+    #[test]
+    fn test_page_base() {
+        let info = impls::X86_64;
+        let base = info.page_base(0x1234_u64.into());
+        assert_eq!(u64::from(base), 0x1000);
+        assert_eq!(info.page_offset(base), 0);
+        assert_eq!(
+            info.calc_page_table_lookup_meta_info(base)
+                .iter()
+                .map(|l| l.index)
+                .collect::<Vec<_>>(),
+            [1, 0, 0, 0]
+        );
+        assert_eq!(
+            u64::from(impls::X86.page_base(0xdead_beef_u64.into())),
+            0xdead_b000
+        );
+    }
 }
@@ -22,111 +22,1232 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+mod blob;
+mod compact;
+mod csv;
+mod diagram;
+mod diff;
+mod heatmap;
+mod json;
+mod rust;
+mod sexp;
+mod svg;
+mod tree;
+
 const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+/// Default label of the page offset, if not customized via
+/// `--page-offset-label`.
+const DEFAULT_PAGE_OFFSET_LABEL: &str = "page offset";
+
 /// Whether ANSI escape sequences should be used or not.
 pub static USE_ANSI: AtomicBool = AtomicBool::new(false);
 
-use crate::addr_width::AddrWidth;
-use crate::cli::{CliArgs, VirtualAddress};
-use crate::page_table_index::PageTableLookupMetaInfo;
-use crate::paging_info::PagingImplInfo;
+use crate::cli::{
+    AddrFormat, Architecture, CliArgs, CliCommand, Endian, LevelNumbering, OffsetBase,
+    OutputFormat, VirtualAddress,
+};
+use crate::pagemap::Pagemap;
 use crate::print::ansi_styles::{paint_heading, paint_hint};
+use crate::translate::{TranslateError, Translation, TranslationStep};
+use paging_calculator::addr_width::AddrWidth;
+use paging_calculator::page_table_index::PageTableLookupMetaInfo;
+use paging_calculator::paging_info::{impls, Half, PagingImplInfo};
+use std::io::{self, Write};
+use std::ops::RangeInclusive;
 use std::sync::atomic::AtomicBool;
 
-fn print_header(paging_info: &PagingImplInfo, v_addr: VirtualAddress) {
-    print!(
+fn print_header(
+    out: &mut dyn Write,
+    cli_input: &CliArgs,
+    paging_info: &PagingImplInfo,
+    v_addr: VirtualAddress,
+) -> io::Result<()> {
+    write!(
+        out,
         "{}",
         paint_heading(&format!(
             "Page Table Calculator (v{}): {}",
             CRATE_VERSION, paging_info.name
         ))
-    );
-    println!();
-    println!("{}", paging_info.description);
-    println!();
+    )?;
+    writeln!(out)?;
+    let mut paragraph = Vec::new();
+    if !cli_input.brief {
+        paragraph.push(paging_info.description.to_string());
+    }
+    if let Some(CliCommand::Architecture(arch)) = cli_input.command {
+        if let Some(huge_page_size) = arch.huge_page_size() {
+            paragraph.push(format!(
+                "The address is part of a {} huge page. The levels below the huge page\n\
+                are part of the page offset.",
+                format_size(huge_page_size)
+            ));
+        }
+    }
+    if !paragraph.is_empty() {
+        writeln!(out, "{}", paragraph.join("\n"))?;
+    }
+    writeln!(out)?;
+    // With a direct map, the physical address is translated to a virtual one.
+    let addr_label = if cli_input.physical && cli_input.directmap_base.is_none() {
+        "address (phys)"
+    } else {
+        "address       "
+    };
     if paging_info.addr_width == AddrWidth::Bits32 {
-        println!(
-            "address       : 0x{:x}  {info}",
-            u64::from(v_addr) & 0xffffffff,
-            info = paint_hint("(user input truncated to 32-bit)")
+        write!(
+            out,
+            "{addr_label}: {}",
+            format_addr(cli_input, paging_info, v_addr.as_u128())
+        )?;
+        if let Some(hint) = addr_width_hint(v_addr, paging_info.addr_width, cli_input.wrap) {
+            write!(out, "  {}", paint_hint(&hint))?;
+        }
+        writeln!(out)?;
+        if cli_input.ruler {
+            writeln!(out, "{:18}{}", "", format_ruler(paging_info.addr_width))?;
+        }
+        write!(
+            out,
+            "address (bits): 0b{:032b}",
+            u64::from(v_addr) & 0xffffffff
+        )?;
+    } else {
+        let addr = paging_info.addr_width.truncate(v_addr.as_u128());
+        let formatted = cli_input.addr_format.map_or_else(
+            || VirtualAddress::from_u128(addr).to_string(),
+            |addr_format| format_addr_as(addr_format, addr, paging_info.addr_width),
         );
-        println!("address (bits): 0b{:032b}", u64::from(v_addr) & 0xffffffff);
+        write!(out, "{addr_label}: {formatted}")?;
+        if let Some(hint) = addr_width_hint(v_addr, paging_info.addr_width, cli_input.wrap) {
+            write!(out, "  {}", paint_hint(&hint))?;
+        }
+        writeln!(out)?;
+        if cli_input.ruler {
+            writeln!(out, "{:18}{}", "", format_ruler(paging_info.addr_width))?;
+        }
+        // The bits above the translated bits are only a sign extension.
+        let width = u64::from(paging_info.addr_width) as usize;
+        let bits = format!("{addr:0width$b}");
+        let (untranslated, translated) = bits.split_at(paging_info.untranslated_bits() as usize);
+        write!(
+            out,
+            "address (bits): 0b{}{translated}",
+            ansi_styles::paint_untranslated(untranslated)
+        )?;
+    }
+    writeln!(
+        out,
+        "  {}",
+        paint_hint(&format!(
+            "(uses {} of {} bits)",
+            paging_info.addr_width.significant_bits(v_addr.as_u128()),
+            u64::from(paging_info.addr_width)
+        ))
+    )?;
+    if cli_input.rainbow {
+        writeln!(
+            out,
+            "address (lvls): {}",
+            format_rainbow(paging_info, v_addr)
+        )?;
+    }
+    if let Some(mem_map) = &cli_input.mem_map {
+        let v_addr = u64::from(v_addr);
+        let phys_addr = cli_input
+            .directmap_base
+            .map_or(v_addr, |base| v_addr.wrapping_sub(u64::from(base)));
+        let memory_type = mem_map
+            .classify(phys_addr)
+            .map_or_else(|| "unknown".to_string(), |t| t.to_string());
+        writeln!(
+            out,
+            "memory type   : {memory_type}  {info}",
+            info = paint_hint("(according to the memory map)")
+        )?;
+    }
+    if cli_input.signed_decimal {
+        writeln!(
+            out,
+            "address (i{}) : {}",
+            u64::from(paging_info.addr_width),
+            paging_info.addr_width.to_signed(v_addr.as_u128())
+        )?;
+    }
+    writeln!(
+        out,
+        "entries per table : {}  {}",
+        paging_info.entries_per_table(),
+        paint_hint(&format!("(2^{})", paging_info.page_table_index_bits))
+    )?;
+    writeln!(
+        out,
+        "page table size   : {} bytes  {}",
+        paging_info.table_size_bytes(),
+        paint_hint(&format!(
+            "({} entries * {} bytes)",
+            paging_info.entries_per_table(),
+            paging_info.page_table_entry_size
+        ))
+    )?;
+    Ok(())
+}
+
+/// Formats a ruler with one column per bit of the address width, from the
+/// most significant bit to bit 0. Every eighth bit position, such as 63, 55,
+/// ..., 7, and bit 0 are labeled in the column of that bit.
+fn format_ruler(addr_width: AddrWidth) -> String {
+    let width = u64::from(addr_width) as usize;
+    let mut ruler = vec![b' '; width];
+    let positions = (0..width).rev().step_by(8).chain([0]);
+    for bit in positions {
+        let column = width - 1 - bit;
+        let label = bit.to_string();
+        let end = (column + label.len()).min(width);
+        ruler[column..end].copy_from_slice(&label.as_bytes()[..end - column]);
+    }
+    String::from_utf8(ruler).unwrap()
+}
+
+/// Returns the warning that the user input doesn't fit into the address
+/// width and is reduced to it: Either truncated (default) or wrapped around,
+/// as some hardware does. The latter explicitly shows the original value.
+/// Returns `None` if the input fits.
+fn addr_width_hint(v_addr: VirtualAddress, addr_width: AddrWidth, wrap: bool) -> Option<String> {
+    if addr_width.fits(v_addr.as_u128()) {
+        return None;
+    }
+    let bits = u64::from(addr_width);
+    let hint = if wrap {
+        format!(
+            "(user input 0x{:x} wrapped around at {bits}-bit)",
+            v_addr.as_u128()
+        )
     } else {
-        println!("address       : {v_addr}");
-        println!("address (bits): 0b{:064b}", u64::from(v_addr));
+        format!("(user input truncated to {bits}-bit)")
+    };
+    Some(hint)
+}
+
+/// Returns the warning that the user input doesn't fit into the address width
+/// and that only the truncated address is walked, for the output formats that
+/// don't show [`addr_width_hint`] next to the address. Returns `None` if the
+/// input fits.
+pub fn truncation_warning(v_addr: VirtualAddress, addr_width: AddrWidth) -> Option<String> {
+    if addr_width.fits(v_addr.as_u128()) {
+        return None;
+    }
+    Some(format!(
+        "Warning: The address 0x{:x} doesn't fit into {} bits, 0x{:x} is walked instead.",
+        v_addr.as_u128(),
+        u64::from(addr_width),
+        addr_width.truncate(v_addr.as_u128())
+    ))
+}
+
+/// Returns whether the walk is printed as regular text, which shows the
+/// [`addr_width_hint`] next to the address.
+fn shows_addr_width_hint(cli_input: &CliArgs) -> bool {
+    !(cli_input.csv_long
+        || cli_input.json_named_levels
+        || cli_input.quiet
+        || cli_input.compact
+        || cli_input.diagram
+        || cli_input.tree
+        || !cli_input.diff.is_empty()
+        || cli_input
+            .format
+            .is_some_and(|format| format != OutputFormat::Text))
+}
+
+/// Formats the address in the notation of `--addr-format`. Without it, the
+/// address is formatted in lowercase hexadecimal without leading zeros.
+fn format_addr(cli_input: &CliArgs, paging_info: &PagingImplInfo, addr: u128) -> String {
+    let addr = paging_info.addr_width.truncate(addr);
+    cli_input.addr_format.map_or_else(
+        || format!("0x{addr:x}"),
+        |addr_format| format_addr_as(addr_format, addr, paging_info.addr_width),
+    )
+}
+
+/// Formats the address with all digits of the address width in the given
+/// notation, such as `FFFF8000`00001000` for [`AddrFormat::Windbg`].
+fn format_addr_as(addr_format: AddrFormat, addr: u128, addr_width: AddrWidth) -> String {
+    let addr = addr_width.truncate(addr);
+    let digits = (u64::from(addr_width) / 4) as usize;
+    match addr_format {
+        AddrFormat::Lower => format!("0x{addr:0digits$x}"),
+        AddrFormat::Upper => format!("0x{addr:0digits$X}"),
+        AddrFormat::Windbg => {
+            let hex = format!("{addr:0digits$X}");
+            hex.as_bytes()
+                .chunks(8)
+                .map(|group| std::str::from_utf8(group).unwrap())
+                .collect::<Vec<_>>()
+                .join("`")
+        }
+    }
+}
+
+/// Formats the offset of an entry into its page table in the given base, such
+/// as `0x0018`, `24`, or `0x18 (24)` for both.
+fn format_entry_offset(offset: u128, base: OffsetBase) -> String {
+    match base {
+        OffsetBase::Hex => format!("0x{offset:04x}"),
+        OffsetBase::Dec => format!("{offset}"),
+        OffsetBase::Both => format!("0x{offset:x} ({offset})"),
     }
 }
 
-/// Prints the information to the screen.
-pub fn print(cli_input: &CliArgs) {
-    let v_addr = cli_input.virtual_address;
-    let paging_impl_info = PagingImplInfo::from_arch(cli_input.architecture);
-    print_header(&paging_impl_info, v_addr);
+/// Swaps the bytes of an offset into a page, i.e., of the smallest number of
+/// bytes that holds the given number of page offset bits. This correlates the
+/// offset with a raw memory dump of a big-endian machine, such as `0x0018`
+/// that reads as `0x1800`.
+fn swap_offset_bytes(offset: u128, page_offset_bits: u64) -> u128 {
+    let bytes = page_offset_bits.div_ceil(8).clamp(1, 16);
+    offset.swap_bytes() >> (128 - 8 * bytes)
+}
+
+/// Returns the label of the page offset: The custom label from the CLI or the
+/// default label.
+fn page_offset_label(cli_input: &CliArgs) -> &str {
+    cli_input
+        .page_offset_label
+        .as_deref()
+        .unwrap_or(DEFAULT_PAGE_OFFSET_LABEL)
+}
+
+/// Formats the line with the page offset, aligned with the per-level lines.
+fn format_page_offset_line(label: &str, offset: u64) -> String {
+    format!("{label:<20}: 0x{offset:x}")
+}
+
+/// Returns the physical address of the entry with the given index in the root
+/// page table at the physical address `root`.
+const fn root_entry_phys_addr(paging_info: &PagingImplInfo, root: u64, index: u64) -> u64 {
+    root.wrapping_add(index.wrapping_mul(paging_info.page_table_entry_size))
+}
+
+/// Returns whether the page table indices of all levels, each shifted by the
+/// shift of its level, and the page offset recompose the translated bits of
+/// the address.
+fn verify_recomposition(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> bool {
+    let recomposed = paging_info
+        .page_table_lookup_meta_info_iter(v_addr)
+        .fold(u128::from(paging_info.page_offset(v_addr)), |addr, info| {
+            addr | u128::from(info.index) << info.shift
+        });
+    let translated_mask = u128::MAX >> (128 - paging_info.translated_bits());
+    recomposed == v_addr.as_u128() & translated_mask
+}
+
+/// Returns the largest natural alignment of the address in bytes, i.e., the
+/// size of the biggest page (regular or huge) that could start at the address.
+/// Returns `None` if the address isn't page-aligned.
+fn alignment_of(v_addr: VirtualAddress, paging_info: &PagingImplInfo) -> Option<u64> {
+    if paging_info.page_offset(v_addr) != 0 {
+        return None;
+    }
+    let level = paging_info
+        .largest_aligned_huge_page_level(v_addr)
+        .unwrap_or(paging_info.leaf_level);
+    Some(paging_info.coverage_per_entry(level))
+}
+
+/// Formats a range of address bits in the notation of hardware manuals, such
+/// as `[47:39]`.
+fn format_bit_range(bits: &RangeInclusive<u64>) -> String {
+    format!("[{}:{}]", bits.end(), bits.start())
+}
+
+/// Formats a size in bytes with the biggest binary unit that represents the
+/// size exactly, such as `4 KiB` or `2 MiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 7] = ["bytes", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+    let mut size = bytes;
+    let mut unit = 0;
+    while size >= 1024 && size & 0x3ff == 0 {
+        size /= 1024;
+        unit += 1;
+    }
+    format!("{size} {}", UNITS[unit])
+}
+
+/// Writes the information for all virtual addresses into `out`, such as stdout.
+pub fn print(
+    out: &mut dyn Write,
+    cli_input: &CliArgs,
+    paging_impl_info: &PagingImplInfo,
+    v_addrs: &[VirtualAddress],
+) -> io::Result<()> {
+    if !shows_addr_width_hint(cli_input) {
+        v_addrs
+            .iter()
+            .chain(cli_input.diff.last())
+            .filter_map(|&v_addr| truncation_warning(v_addr, paging_impl_info.addr_width))
+            .for_each(|warning| eprintln!("{warning}"));
+    }
+
+    if cli_input.verify {
+        for &v_addr in v_addrs {
+            assert!(
+                verify_recomposition(paging_impl_info, v_addr),
+                "the indices of {v_addr} don't recompose the address"
+            );
+        }
+    }
+
+    if cli_input.csv_long {
+        write!(out, "{}", csv::format_csv_long(paging_impl_info, v_addrs))?;
+        return Ok(());
+    }
+
+    if cli_input.json_named_levels || cli_input.format == Some(OutputFormat::Json) {
+        for &v_addr in v_addrs {
+            let json = json::format_json(paging_impl_info, v_addr, cli_input.json_named_levels);
+            writeln!(out, "{json}")?;
+        }
+        return Ok(());
+    }
+
+    if cli_input.format == Some(OutputFormat::Hex) {
+        for &v_addr in v_addrs {
+            writeln!(out, "{}", blob::format_hex(paging_impl_info, v_addr))?;
+        }
+        return Ok(());
+    }
+
+    if cli_input.format == Some(OutputFormat::Binary) {
+        for &v_addr in v_addrs {
+            out.write_all(&blob::encode_walk(paging_impl_info, v_addr))?;
+        }
+        return Ok(());
+    }
+
+    if cli_input.format == Some(OutputFormat::Svg) {
+        for &v_addr in v_addrs {
+            write!(out, "{}", svg::format_svg(paging_impl_info, v_addr))?;
+        }
+        return Ok(());
+    }
+
+    if cli_input.format == Some(OutputFormat::Rust) {
+        for &v_addr in v_addrs {
+            write!(out, "{}", rust::format_rust(paging_impl_info, v_addr))?;
+        }
+        return Ok(());
+    }
+
+    if cli_input.format == Some(OutputFormat::Csv) {
+        write!(out, "{}", csv::format_csv(paging_impl_info, v_addrs))?;
+        return Ok(());
+    }
+
+    if cli_input.format == Some(OutputFormat::Sexp) {
+        for &v_addr in v_addrs {
+            writeln!(out, "{}", sexp::format_sexp(paging_impl_info, v_addr))?;
+        }
+        return Ok(());
+    }
+
+    if cli_input.quiet {
+        for &v_addr in v_addrs {
+            write!(out, "{}", compact::format_quiet(paging_impl_info, v_addr))?;
+        }
+        return Ok(());
+    }
+
+    if cli_input.compact {
+        for &v_addr in v_addrs {
+            writeln!(out, "{}", compact::format_compact(paging_impl_info, v_addr))?;
+        }
+        return Ok(());
+    }
+
+    for (i, &v_addr) in v_addrs.iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
+        }
+        if let Some(&other) = cli_input.diff.last() {
+            write!(
+                out,
+                "{}",
+                diff::format_diff(paging_impl_info, v_addr, other)
+            )?;
+            continue;
+        }
+        if cli_input.diagram {
+            write!(out, "{}", diagram::format_diagram(paging_impl_info, v_addr))?;
+            continue;
+        }
+        if cli_input.tree {
+            let tree = tree::format_tree(paging_impl_info, v_addr, page_offset_label(cli_input));
+            write!(out, "{tree}")?;
+            continue;
+        }
+        print_walk(out, cli_input, paging_impl_info, v_addr)?;
+    }
+
+    if cli_input.heatmap {
+        writeln!(out)?;
+        write!(
+            out,
+            "{}",
+            heatmap::format_heatmap(paging_impl_info, v_addrs)
+        )?;
+    }
+    Ok(())
+}
+
+/// Writes the information for a single virtual address into `out`.
+fn print_walk(
+    out: &mut dyn Write,
+    cli_input: &CliArgs,
+    paging_impl_info: &PagingImplInfo,
+    v_addr: VirtualAddress,
+) -> io::Result<()> {
+    print_header(out, cli_input, paging_impl_info, v_addr)?;
+    if let Err(e) = paging_impl_info.check_canonical(v_addr) {
+        writeln!(
+            out,
+            "{}",
+            paint_hint(&format!(
+                "(warning: non-canonical address, {e}; invalid bits: 0x{:x})",
+                e.invalid_bits
+            ))
+        )?;
+    } else if paging_impl_info.canonical_bounds().higher_half.is_some()
+        // the half of wider addresses isn't determined by their lower 64 bits
+        && paging_impl_info.addr_width != AddrWidth::Bits128
+    {
+        let half = match paging_impl_info.address_half(u64::from(v_addr)) {
+            Half::Lower => "(lower half, typically user space)",
+            Half::Higher => "(higher half, typically kernel space)",
+        };
+        writeln!(out, "{}", paint_hint(half))?;
+        let top = paging_impl_info
+            .page_table_lookup_meta_info_iter(v_addr)
+            .last()
+            .unwrap();
+        if paging_impl_info.top_level_index_half(top.index) == Some(Half::Higher) {
+            let table = paging_impl_info
+                .level_name(top.level)
+                .map_or_else(|| format!("level {}", top.level), ToString::to_string);
+            writeln!(
+                out,
+                "{}",
+                paint_hint(&format!(
+                    "({table} index {} is in the kernel range, which is mostly unmapped from the user page tables with KPTI)",
+                    top.index
+                ))
+            )?;
+        }
+    }
+    if let Some(base) = cli_input.segment_base {
+        writeln!(
+            out,
+            "segment base  : {}  {info}",
+            format_addr(cli_input, paging_impl_info, base.as_u128()),
+            info = paint_hint("(linear address = segment base + offset)")
+        )?;
+    }
+
+    if let Some(base) = cli_input.base {
+        // The direct map is applied after the base.
+        let directmap_base = cli_input.directmap_base.map_or(0, VirtualAddress::as_u128);
+        writeln!(
+            out,
+            "base          : {}  {info}",
+            format_addr(cli_input, paging_impl_info, base.as_u128()),
+            info = paint_hint(&format!(
+                "(effective address = base + address 0x{:x})",
+                v_addr
+                    .as_u128()
+                    .wrapping_sub(directmap_base)
+                    .wrapping_sub(base.as_u128())
+            ))
+        )?;
+    }
+
+    if let Some(base) = cli_input.directmap_base {
+        writeln!(
+            out,
+            "directmap base: {}  {info}",
+            format_addr(cli_input, paging_impl_info, base.as_u128()),
+            info = paint_hint(&format!(
+                "(virtual address = directmap base + physical address 0x{:x})",
+                u64::from(v_addr).wrapping_sub(u64::from(base))
+            ))
+        )?;
+    }
+
+    if cli_input.page_base {
+        writeln!(
+            out,
+            "page base     : {}  {info}",
+            format_addr(cli_input, paging_impl_info, v_addr.as_u128()),
+            info = paint_hint(&format!(
+                "(address aligned down to its {} page)",
+                format_size(paging_impl_info.coverage_per_entry(paging_impl_info.leaf_level))
+            ))
+        )?;
+    }
 
     let page_table_lookup_info = paging_impl_info.calc_page_table_lookup_meta_info(v_addr);
+    let numbering = cli_input.level_numbering.unwrap_or_default();
+    let label = |level| numbering.label(level, paging_impl_info.levels);
 
-    for info in page_table_lookup_info.iter().rev() {
-        print!("level {} bits  : ", info.level);
-        print_relevant_bits_highlighted(info, &paging_impl_info);
-        println!();
+    let order = cli_input.order.unwrap_or_default();
+    let offset_base = if cli_input.offset_both {
+        OffsetBase::Both
+    } else {
+        cli_input.offset_base.unwrap_or_default()
+    };
+    for info in order.apply(&page_table_lookup_info) {
+        write!(out, "level {} bits  : ", label(info.level))?;
+        let absorbed_bits = cli_input.tlb_page_size.map_or(0, |size| {
+            paging_impl_info.tlb_absorbed_bits(info.level, size)
+        });
+        write!(
+            out,
+            "{}",
+            format_relevant_bits_highlighted(info, paging_impl_info, absorbed_bits)
+        )?;
+        let entries = 1_u128 << paging_impl_info.index_bits_at_level(info.level);
+        let coverage = format_size(paging_impl_info.coverage_per_entry(info.level));
+        let mut table = paging_impl_info.level_name(info.level).map_or_else(
+            || format!("{entries} entries of {coverage}"),
+            |name| format!("{name}, {entries} entries of {coverage}"),
+        );
+        if absorbed_bits > 0 {
+            table.push_str(&format!(
+                ", {absorbed_bits} bits absorbed into the {} TLB page offset",
+                format_size(cli_input.tlb_page_size.unwrap())
+            ));
+        }
+        writeln!(
+            out,
+            "  {}  {}",
+            format_bit_range(&paging_impl_info.bit_range(info.level)),
+            paint_hint(&format!("({table})"))
+        )?;
     }
+    writeln!(
+        out,
+        "offset bits   : {}  {}  {}",
+        format_page_offset_bits(paging_impl_info, v_addr),
+        format_bit_range(&paging_impl_info.page_offset_bit_range()),
+        paint_hint(&format!(
+            "({} page)",
+            format_size(paging_impl_info.coverage_per_entry(paging_impl_info.leaf_level))
+        ))
+    )?;
 
-    for (is_first, info) in page_table_lookup_info
-        .iter()
-        .rev()
+    for (is_first, info) in order
+        .apply(&page_table_lookup_info)
+        .into_iter()
         .enumerate()
         .map(|(i, info)| (i == 0, info))
     {
-        print!("level {} entry index : {:>6}", info.level, info.index);
+        write!(
+            out,
+            "level {} entry index : {:>6}",
+            label(info.level),
+            info.index
+        )?;
         if is_first {
-            print!("  {info}", info = paint_hint("(number of entry)"));
+            write!(out, "  {info}", info = paint_hint("(number of entry)"))?;
         }
-        println!();
+        writeln!(out)?;
 
-        print!(
-            "level {} entry offset: 0x{:04x}",
-            info.level,
-            info.index * paging_impl_info.page_table_entry_size
-        );
+        write!(
+            out,
+            "level {} entry offset: {}",
+            label(info.level),
+            format_entry_offset(
+                info.entry_offset(paging_impl_info.page_table_entry_size),
+                offset_base
+            )
+        )?;
+        if cli_input.endian == Some(Endian::Big) {
+            let swapped = swap_offset_bytes(
+                info.entry_offset(paging_impl_info.page_table_entry_size),
+                paging_impl_info.page_offset_bits,
+            );
+            write!(
+                out,
+                "  {}",
+                paint_hint(&format!("(big-endian bytes: 0x{swapped:04x})"))
+            )?;
+        }
         if is_first {
-            print!(
+            write!(
+                out,
                 "  {info}",
                 info = paint_hint("(offset into the page table for that entry)")
+            )?;
+        }
+        writeln!(out)?;
+
+        if let Some(root) = cli_input
+            .root
+            .filter(|_| info.level == paging_impl_info.levels)
+        {
+            write!(
+                out,
+                "level {} entry phys  : 0x{:x}",
+                label(info.level),
+                root_entry_phys_addr(paging_impl_info, u64::from(root), info.index)
+            )?;
+            if is_first {
+                write!(
+                    out,
+                    "  {info}",
+                    info = paint_hint("(root + index * entry size)")
+                )?;
+            }
+            writeln!(out)?;
+        }
+
+        if let Some(cache_line_size) = cli_input.cache_line_size {
+            write!(
+                out,
+                "level {} cache line  : {:>6}",
+                label(info.level),
+                info.cache_line(paging_impl_info.page_table_entry_size, cache_line_size)
+            )?;
+            if is_first {
+                write!(
+                    out,
+                    "  {info}",
+                    info = paint_hint(&format!(
+                        "(cache line of {cache_line_size} bytes within the page table)"
+                    ))
+                )?;
+            }
+            writeln!(out)?;
+        }
+
+        if cli_input.explain {
+            write!(out, "{}", format_shift_explanation(info, paging_impl_info))?;
+            if is_first {
+                write!(
+                    out,
+                    "  {info}",
+                    info = paint_hint(&format!(
+                        "(shift = index bits * (level - {}) + page offset bits)",
+                        paging_impl_info.leaf_level
+                    ))
+                )?;
+            }
+            writeln!(out)?;
+        }
+
+        if let Some(columns) = cli_input.dump_columns {
+            let (row, column) = info.dump_position(columns);
+            write!(
+                out,
+                "level {} dump row/col: row {row}, col {column}",
+                label(info.level)
+            )?;
+            if is_first {
+                write!(
+                    out,
+                    "  {info}",
+                    info = paint_hint(&format!("(position in a {columns}-column hex dump)"))
+                )?;
+            }
+            writeln!(out)?;
+        }
+    }
+
+    write!(
+        out,
+        "{}  {}",
+        format_page_offset_line(
+            page_offset_label(cli_input),
+            paging_impl_info.page_offset(v_addr)
+        ),
+        format_bit_range(&paging_impl_info.page_offset_bit_range())
+    )?;
+    if let Some(alignment) = alignment_of(v_addr, paging_impl_info) {
+        let hint = if alignment == paging_impl_info.coverage_per_entry(paging_impl_info.leaf_level)
+        {
+            "(page-aligned)".to_string()
+        } else {
+            format!("({}-aligned)", format_size(alignment))
+        };
+        write!(out, "  {}", paint_hint(&hint))?;
+    }
+    writeln!(out)?;
+
+    if cli_input.verify {
+        writeln!(
+            out,
+            "verification        : verified  {info}",
+            info = paint_hint("(indices and page offset recompose the translated bits)")
+        )?;
+    }
+
+    if cli_input.packed_vpn {
+        writeln!(
+            out,
+            "packed VPN          : 0x{:x}  {info}",
+            paging_impl_info.packed_vpn(v_addr),
+            info = paint_hint("(concatenated indices of all levels)")
+        )?;
+    }
+
+    if cli_input.trailing_zeros {
+        let trailing_zeros = paging_impl_info
+            .addr_width
+            .trailing_zeros(u64::from(v_addr));
+        writeln!(
+            out,
+            "trailing zeros      : {trailing_zeros}  {info}",
+            info = paint_hint(&format!("(aligned to 2^{trailing_zeros})"))
+        )?;
+    }
+
+    if cli_input.depth {
+        writeln!(
+            out,
+            "depth               : {}  {info}",
+            paging_impl_info.depth(v_addr),
+            info = paint_hint("(number of levels with a non-zero index)")
+        )?;
+    }
+
+    if cli_input.levels_mask {
+        writeln!(
+            out,
+            "levels mask         : 0b{:b}  {info}",
+            paging_impl_info.levels_mask(v_addr),
+            info = paint_hint("(bit i set if level i+1 has a non-zero index)")
+        )?;
+    }
+
+    if cli_input.walk_hash {
+        writeln!(
+            out,
+            "walk hash           : 0x{:016x}  {info}",
+            paging_impl_info.walk_hash(v_addr),
+            info = paint_hint("(FNV-1a of the indices of all levels)")
+        )?;
+    }
+
+    if let (Some(slot), Some(level)) = (cli_input.recursive, cli_input.selfmap_level) {
+        writeln!(
+            out,
+            "self-map addr : 0x{:x}  {info}",
+            paging_impl_info.recursive_table_address(slot, v_addr, level),
+            info = paint_hint(&format!(
+                "(level {} table via recursive slot {slot})",
+                label(level)
+            ))
+        )?;
+    }
+
+    if cli_input.running_sum {
+        let sums = paging_impl_info.running_sums(v_addr);
+        let (final_sum, level_sums) = sums.split_last().unwrap();
+        let digits = (u64::from(paging_impl_info.addr_width) / 4) as usize;
+        for (info, sum) in page_table_lookup_info.iter().rev().zip(level_sums) {
+            writeln!(
+                out,
+                "level {} running sum : 0x{sum:0digits$x}",
+                label(info.level)
+            )?;
+        }
+        writeln!(
+            out,
+            "+ page offset       : 0x{final_sum:0digits$x}  {info}",
+            info = paint_hint("(reconstructed address)")
+        )?;
+    }
+
+    if !cli_input.no_huge_page_hint {
+        if let Some(level) = paging_impl_info.largest_aligned_huge_page_level(v_addr) {
+            let hint = format!(
+                "(this address could be the base of a {} huge page, i.e., the leaf at level {})",
+                format_size(paging_impl_info.coverage_per_entry(level)),
+                label(level)
             );
+            writeln!(out, "{}", paint_hint(&hint))?;
         }
-        println!();
     }
+
+    print_strides(out, paging_impl_info, v_addr, &cli_input.strides, numbering)?;
+
+    if let Some(CliCommand::Architecture(Architecture::X86 {
+        compare_pae: true, ..
+    })) = cli_input.command
+    {
+        writeln!(out)?;
+        print_pae_comparison(out, v_addr)?;
+    }
+    Ok(())
 }
 
-// Prints the relevant bits used for the indexing and highlights them in red.
-// Others are zeroed.
-fn print_relevant_bits_highlighted(info: &PageTableLookupMetaInfo, paging_info: &PagingImplInfo) {
-    let addr_width = u64::from(paging_info.addr_width);
+/// Prints the page table walk of x86 without and with PAE side by side, to
+/// show how the 10/10 bit split becomes a 2/9/9 split.
+fn print_pae_comparison(out: &mut dyn Write, v_addr: VirtualAddress) -> io::Result<()> {
+    let fmt_split = |info: &PagingImplInfo| {
+        info.bit_split()
+            .iter()
+            .map(|bits| bits.to_string())
+            .collect::<Vec<_>>()
+            .join("/")
+    };
+    let x86 = impls::X86.calc_page_table_lookup_meta_info(v_addr);
+    let x86_pae = impls::X86_PAE.calc_page_table_lookup_meta_info(v_addr);
 
-    let zeroes_fill_right_count =
-        paging_info.page_offset_bits + (info.level - 1) * paging_info.page_table_index_bits;
+    writeln!(
+        out,
+        "{}",
+        paint_heading("Comparison: x86 without PAE vs. with PAE")
+    )?;
+    writeln!(
+        out,
+        "                      {:>10}  {:>10}",
+        "x86", "x86 PAE"
+    )?;
+    writeln!(
+        out,
+        "bit split           : {:>10}  {:>10}",
+        fmt_split(&impls::X86),
+        fmt_split(&impls::X86_PAE)
+    )?;
+    for level in (1..=impls::X86_PAE.levels).rev() {
+        let index = |infos: &[PageTableLookupMetaInfo]| {
+            infos
+                .get(level as usize - 1)
+                .map_or_else(|| "-".to_string(), |info| info.index.to_string())
+        };
+        writeln!(
+            out,
+            "level {level} entry index : {:>10}  {:>10}",
+            index(&x86),
+            index(&x86_pae)
+        )?;
+    }
+    writeln!(
+        out,
+        "page offset         : {:>10}  {:>10}",
+        format!("0x{:x}", impls::X86.page_offset(v_addr)),
+        format!("0x{:x}", impls::X86_PAE.page_offset(v_addr))
+    )?;
+    Ok(())
+}
 
-    let page_index_highlight_bits_count =
-        if zeroes_fill_right_count + paging_info.page_table_index_bits > addr_width {
-            addr_width - zeroes_fill_right_count
+/// Prints for each stride the address plus the stride and which levels'
+/// indices changed relative to the address.
+fn print_strides(
+    out: &mut dyn Write,
+    paging_info: &PagingImplInfo,
+    v_addr: VirtualAddress,
+    strides: &[VirtualAddress],
+    numbering: LevelNumbering,
+) -> io::Result<()> {
+    for &stride in strides {
+        let strided_addr = VirtualAddress::from(u64::from(v_addr).wrapping_add(u64::from(stride)));
+        let changed_levels = paging_info.changed_levels(v_addr, strided_addr);
+        write!(out, "stride 0x{:x}: {strided_addr}  ", u64::from(stride))?;
+        if changed_levels.is_empty() {
+            writeln!(out, "{}", paint_hint("(no index changed)"))?;
         } else {
-            paging_info.page_table_index_bits
+            let levels = changed_levels
+                .iter()
+                .rev()
+                .map(|&level| numbering.label(level, paging_info.levels).to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            writeln!(
+                out,
+                "{}",
+                paint_hint(&format!("(changed levels: {levels})"))
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Prints the page table indices of each virtual address of the pagemap next
+/// to the frame it is mapped to, one line per entry.
+pub fn print_pagemap(paging_impl_info: &PagingImplInfo, pagemap: &Pagemap) {
+    print!("{}", format_pagemap(paging_impl_info, pagemap));
+}
+
+/// Formats one line per pagemap entry with the virtual address, the frame,
+/// the page table indices from the top level to level 1, and the page offset.
+fn format_pagemap(paging_info: &PagingImplInfo, pagemap: &Pagemap) -> String {
+    pagemap
+        .entries
+        .iter()
+        .map(|entry| {
+            let indices = paging_info
+                .calc_page_table_lookup_meta_info(entry.v_addr)
+                .iter()
+                .rev()
+                .map(|info| info.index.to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            format!(
+                "{} -> frame 0x{:x}: indices {indices}, offset 0x{:x}\n",
+                entry.v_addr,
+                entry.frame,
+                paging_info.page_offset(entry.v_addr)
+            )
+        })
+        .collect()
+}
+
+/// Prints the ranges of virtual and physical addresses that the raw page table
+/// entry at the given level maps, which is reached by the given path of
+/// indices.
+pub fn print_pte(paging_impl_info: &PagingImplInfo, pte: u64, level: u64, path: &[u64]) {
+    let virt = paging_impl_info.entry_virtual_range(path).unwrap();
+    let size = paging_impl_info.coverage_per_entry(level);
+    let phys_base = paging_impl_info.entry_frame_base(pte, level);
+    let digits = (u64::from(paging_impl_info.addr_width) / 4) as usize;
+    println!(
+        "level {level} entry : 0x{pte:x}  {info}",
+        info = paint_hint(&format!("(maps {})", format_size(size)))
+    );
+    println!(
+        "virtual range : 0x{:0digits$x}..=0x{:0digits$x}",
+        virt.start(),
+        virt.end()
+    );
+    println!(
+        "physical range: 0x{phys_base:x}..=0x{:x}",
+        phys_base + (size - 1)
+    );
+    if pte & 1 == 0 {
+        println!("{}", paint_hint("(the present bit is not set)"));
+    }
+}
+
+/// Prints the entries that were read during the translation of an address
+/// and the resolved physical address, or why the translation stopped. Returns
+/// whether the address was resolved.
+pub fn print_translation(
+    paging_info: &PagingImplInfo,
+    translation: &Result<Translation, TranslateError>,
+) -> bool {
+    let digits = paging_info.page_table_entry_size as usize * 2;
+    let print_step = |step: &TranslationStep| {
+        println!(
+            "level {} entry : 0x{:0digits$x}  {}",
+            step.level,
+            step.entry,
+            paint_hint(&format!("(at 0x{:x})", step.entry_addr))
+        );
+    };
+    match translation {
+        Ok(translation) => {
+            translation.steps.iter().for_each(print_step);
+            println!(
+                "physical addr : 0x{:x}  {}",
+                translation.phys_addr,
+                paint_hint(&format!(
+                    "({} page mapped at level {})",
+                    format_size(paging_info.coverage_per_entry(translation.leaf_level)),
+                    translation.leaf_level
+                ))
+            );
+            true
+        }
+        Err(e) => {
+            eprintln!("{e}");
+            false
+        }
+    }
+}
+
+/// Prints the virtual address at which the page table of the given level is
+/// accessible through the recursive self-map at `slot`.
+pub fn print_selfmap_frame(addr: u64, level: u64, slot: u64) {
+    println!(
+        "self-map addr : 0x{addr:x}  {}",
+        paint_hint(&format!("(level {level} table via recursive slot {slot})"))
+    );
+}
+
+/// Prints a table of the given paging implementations and their parameters.
+pub fn print_list(paging_infos: &[PagingImplInfo]) {
+    print!("{}", format_list(paging_infos));
+}
+
+/// Formats a table with one row per paging implementation, listing the
+/// number of levels, index bits, page offset bits, and the entry size.
+fn format_list(paging_infos: &[PagingImplInfo]) -> String {
+    let name_width = paging_infos
+        .iter()
+        .map(|info| info.name.len())
+        .chain(["name".len()])
+        .max()
+        .unwrap();
+    let mut list = format!(
+        "{:name_width$}  levels  index bits  offset bits  entry size\n",
+        "name"
+    );
+    for info in paging_infos {
+        list += &format!(
+            "{:name_width$}  {:>6}  {:>10}  {:>11}  {:>10}\n",
+            info.name,
+            info.levels,
+            info.page_table_index_bits,
+            info.page_offset_bits,
+            info.page_table_entry_size
+        );
+    }
+    list
+}
+
+/// Prints the minimal and maximal canonical virtual addresses of the given
+/// paging implementation to the screen.
+pub fn print_bounds(paging_impl_info: &PagingImplInfo, json: bool) {
+    print!("{}", format_bounds(paging_impl_info, json));
+}
+
+/// Formats the canonical address bounds of the paging implementation either
+/// as human-readable text or as JSON.
+fn format_bounds(paging_info: &PagingImplInfo, json: bool) -> String {
+    let bounds = paging_info.canonical_bounds();
+    let digits = (u64::from(paging_info.addr_width) / 4) as usize;
+    let fmt_addr = |addr: u128| format!("0x{addr:0digits$x}");
+    if json {
+        let fmt_range = |range: &RangeInclusive<u128>| {
+            format!(
+                "{{\"min\":\"{}\",\"max\":\"{}\"}}",
+                fmt_addr(*range.start()),
+                fmt_addr(*range.end())
+            )
         };
+        format!(
+            "{{\"arch\":\"{}\",\"lower_half\":{},\"higher_half\":{}}}\n",
+            paging_info.name,
+            fmt_range(&bounds.lower_half),
+            bounds
+                .higher_half
+                .as_ref()
+                .map_or_else(|| "null".to_string(), fmt_range)
+        )
+    } else {
+        let mut text = format!(
+            "lower half : {} - {}\n",
+            fmt_addr(*bounds.lower_half.start()),
+            fmt_addr(*bounds.lower_half.end())
+        );
+        if let Some(higher_half) = bounds.higher_half {
+            text += &format!(
+                "higher half: {} - {}\n",
+                fmt_addr(*higher_half.start()),
+                fmt_addr(*higher_half.end())
+            );
+        }
+        text
+    }
+}
+
+// Formats the relevant bits used for the indexing and highlights them in red.
+// Others are zeroed. The page offset bits are colored green.
+fn format_relevant_bits_highlighted(
+    info: &PageTableLookupMetaInfo,
+    paging_info: &PagingImplInfo,
+    absorbed_bits: u64,
+) -> String {
+    let addr_width = u64::from(paging_info.addr_width);
+
+    let zeroes_fill_right_count = *paging_info.bit_range(info.level).start();
+
+    let page_index_highlight_bits_count = paging_info.index_bits_at_level(info.level);
 
     let zeroes_fill_left_count =
         addr_width - zeroes_fill_right_count - page_index_highlight_bits_count;
 
-    print!(
-        "0b{zeroes_left_fill}{highlighted_index}{zeroes_right_fill}",
+    // The lowest `absorbed_bits` bits of the index are part of the offset of a
+    // bigger TLB page and get a different highlight.
+    let index_bits = format!(
+        "{index:0bits$b}",
+        index = info.index,
+        bits = page_index_highlight_bits_count as usize
+    );
+    let (index_bits, absorbed_index_bits) =
+        index_bits.split_at((page_index_highlight_bits_count - absorbed_bits) as usize);
+
+    // The page offset bits are always zero here, but get their own color to
+    // separate them visually from the index bits of the lower levels.
+    let offset_bits = "0".repeat(paging_info.page_offset_bits as usize);
+    format!(
+        "0b{zeroes_left_fill}{highlighted_index}{absorbed_index}{zeroes_right_fill}{offset}",
         zeroes_left_fill = "0".repeat(zeroes_fill_left_count as usize),
-        highlighted_index = ansi_styles::paint_highlight(&format!(
-            "{index:0bits$b}",
-            index = info.index,
-            bits = page_index_highlight_bits_count as usize
-        )),
-        zeroes_right_fill = "0".repeat(zeroes_fill_right_count as usize)
+        highlighted_index = ansi_styles::paint_highlight(index_bits),
+        absorbed_index = ansi_styles::paint_absorbed(absorbed_index_bits),
+        zeroes_right_fill =
+            "0".repeat((zeroes_fill_right_count - paging_info.page_offset_bits) as usize),
+        offset = ansi_styles::paint_offset(&offset_bits)
+    )
+}
+
+/// Formats the address in binary, such as the `address (bits)` line, but with
+/// the index bits of each level in their own color, from the top level to
+/// level 1, and the page offset in gray. The untranslated upper bits are
+/// dimmed.
+fn format_rainbow(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    let width = u64::from(paging_info.addr_width) as usize;
+    let bits = format!(
+        "{addr:0width$b}",
+        addr = paging_info.addr_width.truncate(v_addr.as_u128())
     );
+    let (untranslated, mut rest) = bits.split_at(paging_info.untranslated_bits() as usize);
+    let mut line = format!("0b{}", ansi_styles::paint_untranslated(untranslated));
+    for (i, level) in (paging_info.leaf_level..=paging_info.levels)
+        .rev()
+        .enumerate()
+    {
+        let (index_bits, remaining) =
+            rest.split_at(paging_info.index_bits_at_level(level) as usize);
+        line.push_str(&ansi_styles::paint_level(index_bits, i).to_string());
+        rest = remaining;
+    }
+    line.push_str(&ansi_styles::paint_gray(rest).to_string());
+    line
+}
+
+/// Formats how the shift of the level is derived, such as
+/// `level 3 shift = 9*(3-1)+12 = 30` on x86_64.
+fn format_shift_explanation(
+    info: &PageTableLookupMetaInfo,
+    paging_info: &PagingImplInfo,
+) -> String {
+    format!(
+        "level {level} shift = {index_bits}*({level}-{leaf_level})+{page_offset_bits} = {shift}",
+        level = info.level,
+        leaf_level = paging_info.leaf_level,
+        index_bits = paging_info.page_table_index_bits,
+        page_offset_bits = paging_info.page_offset_bits,
+        shift = info.shift
+    )
+}
+
+/// Formats the address bits with only the page offset bits set, such as the
+/// level lines of the bit diagram. The offset bits are highlighted in the
+/// same color as in the level lines.
+fn format_page_offset_bits(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    let offset_bits = format!(
+        "{offset:0bits$b}",
+        offset = paging_info.page_offset(v_addr),
+        bits = paging_info.page_offset_bits as usize
+    );
+    format!(
+        "0b{zeroes_left_fill}{highlighted_offset}",
+        zeroes_left_fill =
+            "0".repeat((u64::from(paging_info.addr_width) - paging_info.page_offset_bits) as usize),
+        highlighted_offset = ansi_styles::paint_offset(&offset_bits)
+    )
 }
 
 mod ansi_styles {
@@ -142,6 +1263,60 @@ mod ansi_styles {
         }
     }
 
+    pub fn paint_absorbed(str: &str) -> AnsiGenericString<'_, str> {
+        if USE_ANSI.load(Ordering::SeqCst) {
+            Style::new().fg(Color::Yellow).bold().paint(str)
+        } else {
+            Style::new().paint(str)
+        }
+    }
+
+    pub fn paint_offset(str: &str) -> AnsiGenericString<'_, str> {
+        if USE_ANSI.load(Ordering::SeqCst) {
+            Style::new().fg(Color::Green).paint(str)
+        } else {
+            Style::new().paint(str)
+        }
+    }
+
+    pub fn paint_untranslated(str: &str) -> AnsiGenericString<'_, str> {
+        if USE_ANSI.load(Ordering::SeqCst) {
+            Style::new().dimmed().paint(str)
+        } else {
+            Style::new().paint(str)
+        }
+    }
+
+    /// Colors of the index bits of the levels in the `--rainbow` line, from
+    /// the top level downwards. Repeats for more levels.
+    const LEVEL_PALETTE: [Color; 5] = [
+        Color::Blue,
+        Color::Green,
+        Color::Magenta,
+        Color::Cyan,
+        Color::Yellow,
+    ];
+
+    /// Paints the index bits of the `i`-th level, counted from the top level.
+    pub fn paint_level(str: &str, i: usize) -> AnsiGenericString<'_, str> {
+        if USE_ANSI.load(Ordering::SeqCst) {
+            Style::new()
+                .fg(LEVEL_PALETTE[i % LEVEL_PALETTE.len()])
+                .bold()
+                .paint(str)
+        } else {
+            Style::new().paint(str)
+        }
+    }
+
+    pub fn paint_gray(str: &str) -> AnsiGenericString<'_, str> {
+        if USE_ANSI.load(Ordering::SeqCst) {
+            Style::new().fg(Color::DarkGray).paint(str)
+        } else {
+            Style::new().paint(str)
+        }
+    }
+
     pub fn paint_heading(str: &str) -> AnsiGenericString<'_, str> {
         if USE_ANSI.load(Ordering::SeqCst) {
             Style::new().bold().paint(str)
@@ -158,3 +1333,409 @@ mod ansi_styles {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncation_warning() {
+        let v_addr = VirtualAddress::from_u128(0x1_0000_0000_dead_beef);
+        assert_eq!(
+            truncation_warning(v_addr, AddrWidth::Bits64).as_deref(),
+            Some(
+                "Warning: The address 0x100000000deadbeef doesn't fit into 64 bits, \
+                0xdeadbeef is walked instead."
+            )
+        );
+        assert_eq!(truncation_warning(v_addr, AddrWidth::Bits128), None);
+        assert!(truncation_warning(0x1_dead_beef.into(), AddrWidth::Bits32).is_some());
+        assert_eq!(
+            truncation_warning(0xdead_beef.into(), AddrWidth::Bits32),
+            None
+        );
+    }
+
+    #[test]
+    fn test_shows_addr_width_hint() {
+        use clap::Parser;
+
+        let cli = CliArgs::parse_from(["pc", "0x1", "x86_64"]);
+        assert!(shows_addr_width_hint(&cli));
+        let cli = CliArgs::parse_from(["pc", "0x1", "x86_64", "--format", "text"]);
+        assert!(shows_addr_width_hint(&cli));
+        for flag in [
+            &["--compact"][..],
+            &["--quiet"],
+            &["--tree"],
+            &["--format", "json"],
+        ] {
+            let cli = CliArgs::parse_from(
+                ["pc", "0x1", "x86_64"]
+                    .into_iter()
+                    .chain(flag.iter().copied()),
+            );
+            assert!(!shows_addr_width_hint(&cli), "{flag:?}");
+        }
+    }
+
+    #[test]
+    fn test_addr_width_hint() {
+        let v_addr = 0x1_dead_beef.into();
+        assert_eq!(
+            addr_width_hint(v_addr, AddrWidth::Bits32, false).as_deref(),
+            Some("(user input truncated to 32-bit)")
+        );
+        assert_eq!(
+            addr_width_hint(v_addr, AddrWidth::Bits32, true).as_deref(),
+            Some("(user input 0x1deadbeef wrapped around at 32-bit)")
+        );
+
+        let v_addr = 0x1_0000_0000.into();
+        assert!(addr_width_hint(v_addr, AddrWidth::Bits32, false).is_some());
+        assert_eq!(addr_width_hint(v_addr, AddrWidth::Bits64, false), None);
+        assert_eq!(
+            addr_width_hint(0xdead_beef.into(), AddrWidth::Bits32, false),
+            None
+        );
+    }
+
+    #[test]
+    fn test_format_entry_offset() {
+        let offset = u128::from(3 * impls::X86_64.page_table_entry_size);
+        assert_eq!(format_entry_offset(offset, OffsetBase::Hex), "0x0018");
+        assert_eq!(format_entry_offset(offset, OffsetBase::Dec), "24");
+        assert_eq!(format_entry_offset(offset, OffsetBase::Both), "0x18 (24)");
+        assert_eq!(format_entry_offset(0, OffsetBase::Dec), "0");
+    }
+
+    #[test]
+    fn test_swap_offset_bytes() {
+        assert_eq!(swap_offset_bytes(0x18, 12), 0x1800);
+        assert_eq!(swap_offset_bytes(0xff8, 12), 0xf80f);
+        assert_eq!(swap_offset_bytes(0x12_3456, 21), 0x56_3412);
+        assert_eq!(swap_offset_bytes(0x8, 3), 0x8);
+    }
+
+    /// Removes the ANSI escape sequences, which depend on [`USE_ANSI`].
+    fn strip_ansi(str: &str) -> String {
+        let mut stripped = String::new();
+        let mut chars = str.chars();
+        while let Some(c) = chars.next() {
+            if c == '\x1b' {
+                chars.by_ref().find(|&c| c == 'm');
+            } else {
+                stripped.push(c);
+            }
+        }
+        stripped
+    }
+
+    #[test]
+    fn test_format_rainbow() {
+        let line = strip_ansi(&format_rainbow(&impls::X86_64, 0xdead_beef.into()));
+        assert_eq!(line.len(), 2 + 64);
+        assert_eq!(line, format!("0b{:064b}", 0xdead_beef_u64));
+
+        let line = strip_ansi(&format_rainbow(&impls::X86_PAE, 0xdead_beef.into()));
+        assert_eq!(line.len(), 2 + 32);
+        assert_eq!(line, "0b11011110101011011011111011101111");
+    }
+
+    #[test]
+    fn test_format_relevant_bits_highlighted() {
+        let info = impls::X86_64;
+        let levels = info.calc_page_table_lookup_meta_info(0xdead_beef.into());
+        let bits = strip_ansi(&format_relevant_bits_highlighted(&levels[0], &info, 0));
+        assert_eq!(bits.len(), 2 + 64);
+        // level 1 index 219, followed by the 12-bit page offset region
+        assert!(bits.ends_with("011011011000000000000"));
+
+        let info = impls::X86;
+        let levels = info.calc_page_table_lookup_meta_info(0xdead_beef.into());
+        let bits = strip_ansi(&format_relevant_bits_highlighted(&levels[1], &info, 0));
+        assert_eq!(bits, "0b11011110100000000000000000000000");
+    }
+
+    #[test]
+    fn test_format_page_offset_line() {
+        assert_eq!(
+            format_page_offset_line(DEFAULT_PAGE_OFFSET_LABEL, 0xeef),
+            "page offset         : 0xeef"
+        );
+        assert_eq!(
+            format_page_offset_line("section offset", 0xeef),
+            "section offset      : 0xeef"
+        );
+    }
+
+    #[test]
+    fn test_format_page_offset_bits() {
+        assert_eq!(
+            format_page_offset_bits(&impls::X86, 0xdead_beef.into()),
+            "0b00000000000000000000111011101111"
+        );
+        let bits = format_page_offset_bits(&impls::X86_64, 0x1234.into());
+        assert_eq!(bits.len(), 2 + 64);
+        assert!(bits.ends_with("001000110100"));
+        assert_eq!(bits[2..].trim_start_matches('0'), "1000110100");
+    }
+
+    #[test]
+    fn test_format_shift_explanation() {
+        let infos = impls::X86_64.calc_page_table_lookup_meta_info(0xdead_beef.into());
+        assert_eq!(
+            format_shift_explanation(&infos[2], &impls::X86_64),
+            "level 3 shift = 9*(3-1)+12 = 30"
+        );
+    }
+
+    #[test]
+    fn test_format_bit_range() {
+        let x86_64 = impls::X86_64;
+        assert_eq!(format_bit_range(&x86_64.bit_range(4)), "[47:39]");
+        assert_eq!(format_bit_range(&x86_64.bit_range(1)), "[20:12]");
+        assert_eq!(format_bit_range(&x86_64.page_offset_bit_range()), "[11:0]");
+    }
+
+    #[test]
+    fn test_format_pagemap() {
+        let pagemap = "0xdead_beef 0x1a2b3\n0xffff_8000_0000_1000 0x42"
+            .parse()
+            .unwrap();
+        let output = format_pagemap(&impls::X86_64, &pagemap);
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            [
+                "0x00000000deadbeef -> frame 0x1a2b3: indices 0/3/245/219, offset 0xeef",
+                "0xffff800000001000 -> frame 0x42: indices 256/0/0/1, offset 0x0",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 bytes");
+        assert_eq!(format_size(24), "24 bytes");
+        assert_eq!(format_size(4096), "4 KiB");
+        assert_eq!(format_size(0x20_0000), "2 MiB");
+        assert_eq!(format_size(0x80_0000_0000), "512 GiB");
+        assert_eq!(format_size(0x1800), "6 KiB");
+    }
+
+    #[test]
+    fn test_format_addr_as() {
+        let addr = 0xffff_8000_0000_1000;
+        let format = |addr_format| format_addr_as(addr_format, addr, AddrWidth::Bits64);
+        assert_eq!(format(AddrFormat::Lower), "0xffff800000001000");
+        assert_eq!(format(AddrFormat::Upper), "0xFFFF800000001000");
+        assert_eq!(format(AddrFormat::Windbg), "FFFF8000`00001000");
+        assert_eq!(
+            format_addr_as(AddrFormat::Windbg, 0xdead_beef, AddrWidth::Bits32),
+            "DEADBEEF"
+        );
+    }
+
+    #[test]
+    fn test_format_ruler() {
+        let ruler = format_ruler(AddrWidth::Bits64);
+        assert_eq!(ruler.len(), format!("{:064b}", 0).len());
+        assert!(ruler.starts_with("63      55      47"));
+        assert!(ruler.ends_with("7      0"));
+        assert_eq!(format_ruler(AddrWidth::Bits32).len(), 32);
+        assert!(format_ruler(AddrWidth::Bits32).starts_with("31      23"));
+    }
+
+    #[test]
+    fn test_root_entry_phys_addr() {
+        // 0xdeadbeef uses index 0 in the PML4 and index 890 in the x86 PD
+        assert_eq!(root_entry_phys_addr(&impls::X86_64, 0x1000, 0), 0x1000);
+        assert_eq!(
+            root_entry_phys_addr(&impls::X86_64, 0x1_2000, 511),
+            0x1_2ff8
+        );
+        assert_eq!(root_entry_phys_addr(&impls::X86, 0x5000, 890), 0x5de8);
+    }
+
+    #[test]
+    fn test_verify_recomposition() {
+        let v_addrs = [0x0, 0xfff, 0xdead_beef, 0xffff_8000_dead_beef, u64::MAX];
+        for info in impls::ALL {
+            for v_addr in v_addrs {
+                assert!(verify_recomposition(info, v_addr.into()), "{v_addr:#x}");
+            }
+        }
+        let info = PagingImplInfo::generic(128, 4, 16, 12, 8).unwrap();
+        let v_addr = VirtualAddress::from_u128(0xabc << 64 | 0xdead_beef);
+        assert!(verify_recomposition(&info, v_addr));
+    }
+
+    #[test]
+    fn test_alignment_of() {
+        let alignment = |addr: u64| alignment_of(addr.into(), &impls::X86_64);
+        assert_eq!(alignment(0x1000), Some(0x1000));
+        assert_eq!(alignment(0x20_0000), Some(0x20_0000));
+        assert_eq!(alignment(0x4000_0000), Some(0x4000_0000));
+        assert_eq!(alignment(0x1234), None);
+    }
+
+    #[test]
+    fn test_format_list() {
+        let list = format_list(impls::ALL);
+        assert!(list.contains("x86_64 paging"));
+        assert_eq!(list.lines().count(), 1 + impls::ALL.len());
+        assert!(list.starts_with("name "));
+    }
+
+    #[test]
+    fn test_format_bounds() {
+        assert_eq!(
+            format_bounds(&impls::X86_64, false),
+            "lower half : 0x0000000000000000 - 0x00007fffffffffff\n\
+            higher half: 0xffff800000000000 - 0xffffffffffffffff\n"
+        );
+        assert_eq!(
+            format_bounds(&impls::X86, true),
+            "{\"arch\":\"x86 32-bit paging\",\
+            \"lower_half\":{\"min\":\"0x00000000\",\"max\":\"0xffffffff\"},\
+            \"higher_half\":null}\n"
+        );
+    }
+
+    #[test]
+    fn test_color_never_has_no_escape_bytes() {
+        crate::configure_ansi_setting(crate::cli::ColorOption::Never);
+        let info = &impls::X86_64.calc_page_table_lookup_meta_info(0xdead_beef.into())[1];
+        let bits = format_relevant_bits_highlighted(info, &impls::X86_64, 0);
+        assert!(!bits.contains('\x1b'));
+        assert!(!paint_hint("(hint)").to_string().contains('\x1b'));
+    }
+
+    #[test]
+    fn test_print_into_buffer() {
+        use clap::Parser;
+
+        let cli = CliArgs::parse_from(["pc", "0xdeadbeef", "0x1000", "x86_64", "--compact"]);
+        let mut out = Vec::new();
+        print(&mut out, &cli, &impls::X86_64, &cli.virtual_addresses).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "L4=0x0 L3=0x3 L2=0xf5 L1=0xdb off=0xeef\n\
+            L4=0x0 L3=0x0 L2=0x0 L1=0x1 off=0x0\n"
+        );
+    }
+
+    #[test]
+    fn test_print_packed_vpn_of_128_bit_address() {
+        use clap::Parser;
+
+        crate::configure_ansi_setting(crate::cli::ColorOption::Never);
+        let cli = CliArgs::parse_from([
+            "pc",
+            "generic",
+            "128",
+            "2",
+            "40",
+            "12",
+            "8",
+            "0x10_0000_0000_0000_1000",
+            "--packed-vpn",
+            "--signed-decimal",
+        ]);
+        let info = PagingImplInfo::generic(128, 2, 40, 12, 8).unwrap();
+        let v_addr = VirtualAddress::from_u128(0x10_0000_0000_0000_1000);
+        let mut out = Vec::new();
+        print(&mut out, &cli, &info, &[v_addr]).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(
+            out.contains("packed VPN          : 0x100000000000001 "),
+            "{out}"
+        );
+        assert!(
+            out.contains(&format!(
+                "address (i128) : {}",
+                0x10_0000_0000_0000_1000_u128
+            )),
+            "{out}"
+        );
+    }
+
+    #[test]
+    fn test_print_verifies_every_format() {
+        use clap::Parser;
+
+        for format in ["text", "json", "csv", "hex", "sexp"] {
+            let cli = CliArgs::parse_from([
+                "pc",
+                "0xdeadbeef",
+                "0xffff_8000_0000_1000",
+                "x86_64",
+                "--verify",
+                "--format",
+                format,
+            ]);
+            let mut out = Vec::new();
+            print(&mut out, &cli, &impls::X86_64, &cli.virtual_addresses).unwrap();
+            assert!(!out.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_print_huge_page_keeps_level_numbers() {
+        use clap::Parser;
+
+        let cli = CliArgs::parse_from([
+            "pc",
+            "0xffff800000401234",
+            "x86_64",
+            "--page-size",
+            "2m",
+            "--compact",
+        ]);
+        let info = impls::X86_64.with_page_size(0x20_0000).unwrap();
+        let mut out = Vec::new();
+        print(&mut out, &cli, &info, &cli.virtual_addresses).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "L4=0x100 L3=0x0 L2=0x2 off=0x1234\n"
+        );
+    }
+
+    #[test]
+    fn test_print_entry_offset_with_64_index_bits() {
+        use clap::Parser;
+
+        crate::configure_ansi_setting(crate::cli::ColorOption::Never);
+        let info = PagingImplInfo::generic(128, 1, 64, 12, 8).unwrap();
+        for format in [&[][..], &["--format", "json"], &["--format", "csv"]] {
+            let cli = CliArgs::parse_from(
+                ["pc", "generic", "128", "1", "64", "12", "8", "0x0"]
+                    .into_iter()
+                    .chain(format.iter().copied()),
+            );
+            let v_addr = VirtualAddress::from_u128(0xfff_ffff_ffff_ffff_f000);
+            let mut out = Vec::new();
+            print(&mut out, &cli, &info, &[v_addr]).unwrap();
+            let out = String::from_utf8(out).unwrap();
+            assert!(
+                out.contains("0x7fffffffffffffff8") || out.contains("147573952589676412920"),
+                "{out}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_print_x86_64_golden() {
+        use clap::Parser;
+
+        crate::configure_ansi_setting(crate::cli::ColorOption::Never);
+        let cli = CliArgs::parse_from(["pc", "0xdead_beef", "x86_64"]);
+        let mut out = Vec::new();
+        print(&mut out, &cli, &impls::X86_64, &cli.virtual_addresses).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            include_str!("../test_res/0xdeadbeef_x86_64.stdout.txt")
+        );
+    }
+}
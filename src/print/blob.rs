@@ -0,0 +1,112 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Compact binary encoding of the page table lookup information.
+//!
+//! The layout is one little-endian unsigned integer per level, ordered from
+//! the top level to level 1. Each integer is the smallest of `u16`, `u32`, and
+//! `u64` that holds the index bits of its level. Hence, the size of the blob
+//! only depends on the paging implementation. For all known paging
+//! implementations, this is one `u16` per level, i.e., `2 * levels` bytes. For
+//! example, the walk of `0xdeadbeef` on x86_64 (indices 0/3/245/219) is encoded
+//! as `00000300f500db00`. Custom paging implementations may have up to 2^64
+//! entries per table.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+
+/// Encodes the page table indices of the address according to the layout
+/// described in the module documentation.
+pub fn encode_walk(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> Vec<u8> {
+    paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+        .flat_map(|info| {
+            let size = field_size(paging_info.index_bits_at_level(info.level));
+            info.index.to_le_bytes().into_iter().take(size)
+        })
+        .collect()
+}
+
+/// Returns the size in bytes of the field that holds an index of the given
+/// number of bits.
+const fn field_size(index_bits: u64) -> usize {
+    match index_bits {
+        0..=16 => 2,
+        17..=32 => 4,
+        _ => 8,
+    }
+}
+
+/// Formats the encoded walk of the address as lowercase hex string.
+pub fn format_hex(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    encode_walk(paging_info, v_addr)
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_format_hex_decodes_to_indices() {
+        let hex = format_hex(&impls::X86_64, 0xdead_beef.into());
+        assert_eq!(hex, "00000300f500db00");
+
+        let bytes = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect::<Vec<_>>();
+        let indices = bytes
+            .chunks_exact(2)
+            .map(|le| u16::from_le_bytes([le[0], le[1]]))
+            .collect::<Vec<_>>();
+        assert_eq!(indices, [0, 3, 245, 219]);
+    }
+
+    #[test]
+    fn test_encode_walk_with_20_bit_level() {
+        let info = PagingImplInfo::generic(64, 1, 20, 12, 8).unwrap();
+        assert_eq!(format_hex(&info, 0xffff_f000.into()), "ffff0f00");
+
+        let info = PagingImplInfo::generic(64, 2, 20, 12, 8).unwrap();
+        let blob = encode_walk(&info, 0x1_0000_2000.into());
+        assert_eq!(blob.len(), 2 * 4);
+        assert_eq!(blob, [0x01, 0, 0, 0, 0x02, 0, 0, 0]);
+
+        let info = PagingImplInfo::generic(128, 1, 64, 12, 8).unwrap();
+        assert_eq!(encode_walk(&info, 0x1000.into()), 1_u64.to_le_bytes());
+    }
+
+    #[test]
+    fn test_encode_walk_has_fixed_size() {
+        let blob = encode_walk(&impls::X86, 0xdead_beef.into());
+        assert_eq!(blob.len(), 2 * impls::X86.levels as usize);
+        assert_eq!(blob, [0x7a, 0x03, 0xdb, 0x02]);
+    }
+}
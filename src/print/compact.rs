@@ -0,0 +1,85 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Terse output of the page table lookup information for shell scripts.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fmt::Write;
+
+/// Formats the page table indices from the top level to level 1 and the page
+/// offset as a single line without descriptions or colors, such as
+/// `L4=0x0 L3=0x3 L2=0xf5 L1=0xdb off=0xeef`. This is handy for shell
+/// scripts.
+pub fn format_compact(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    let mut line = String::new();
+    for info in paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+    {
+        write!(line, "L{}=0x{:x} ", info.level, info.index).unwrap();
+    }
+    write!(line, "off=0x{:x}", paging_info.page_offset(v_addr)).unwrap();
+    line
+}
+
+/// Formats only the page table indices, one per line, from the top level to
+/// level 1. Unlike [`format_compact`], there are no labels and no page
+/// offset.
+pub fn format_quiet(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+        .map(|info| format!("{}\n", info.index))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_format_compact() {
+        assert_eq!(
+            format_compact(&impls::X86_64, 0xdead_beef.into()),
+            "L4=0x0 L3=0x3 L2=0xf5 L1=0xdb off=0xeef"
+        );
+        assert_eq!(
+            format_compact(&impls::X86, 0xdead_beef.into()),
+            "L2=0x37a L1=0x2db off=0xeef"
+        );
+    }
+
+    #[test]
+    fn test_format_quiet() {
+        assert_eq!(
+            format_quiet(&impls::X86_64, 0xdead_beef.into()),
+            "0\n3\n245\n219\n"
+        );
+        assert_eq!(format_quiet(&impls::X86, 0xdead_beef.into()), "890\n731\n");
+    }
+}
@@ -0,0 +1,129 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! CSV output of the page table lookup information.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fmt::Write;
+
+/// Header row of the long-format CSV. The column set is stable so that the
+/// output can be loaded directly via `pandas.read_csv`.
+const CSV_LONG_HEADER: &str = "address,arch,level,index,offset,shift,coverage_bytes";
+
+/// Header row of the per-level CSV.
+const CSV_HEADER: &str = "level,index,index_hex,shift,entry_offset,coverage_bytes";
+
+/// Formats the page table lookup information of all given addresses as CSV
+/// with one row per level, from the top level to level 1. Unlike
+/// [`format_csv_long`], the rows don't contain the address.
+pub fn format_csv(paging_info: &PagingImplInfo, v_addrs: &[VirtualAddress]) -> String {
+    let mut csv = String::new();
+    writeln!(csv, "{CSV_HEADER}").unwrap();
+    for &v_addr in v_addrs {
+        for info in paging_info
+            .calc_page_table_lookup_meta_info(v_addr)
+            .iter()
+            .rev()
+        {
+            writeln!(
+                csv,
+                "{level},{index},0x{index:x},{shift},{offset},{coverage}",
+                level = info.level,
+                index = info.index,
+                shift = info.shift,
+                offset = info.entry_offset(paging_info.page_table_entry_size),
+                coverage = paging_info.coverage_per_entry(info.level),
+            )
+            .unwrap();
+        }
+    }
+    csv
+}
+
+/// Formats the page table lookup information of all given addresses as CSV in
+/// "long format", i.e., with one row per (address, level) pair. Levels are
+/// ordered from the top level to level 1 for each address.
+pub fn format_csv_long(paging_info: &PagingImplInfo, v_addrs: &[VirtualAddress]) -> String {
+    let mut csv = String::new();
+    writeln!(csv, "{CSV_LONG_HEADER}").unwrap();
+    for &v_addr in v_addrs {
+        let addr = paging_info.addr_width.truncate(v_addr.as_u128());
+        for info in paging_info
+            .calc_page_table_lookup_meta_info(v_addr)
+            .iter()
+            .rev()
+        {
+            writeln!(
+                csv,
+                "0x{addr:x},{arch},{level},{index},{offset},{shift},{coverage}",
+                arch = paging_info.name,
+                level = info.level,
+                index = info.index,
+                offset = info.entry_offset(paging_info.page_table_entry_size),
+                shift = info.shift,
+                coverage = paging_info.coverage_per_entry(info.level),
+            )
+            .unwrap();
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_format_csv_has_one_row_per_level() {
+        let csv = format_csv(&impls::X86_64, &[0xdead_beef.into()]);
+        let mut lines = csv.lines();
+
+        assert_eq!(lines.next(), Some(CSV_HEADER));
+        let rows = lines
+            .map(|line| line.split(',').collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert!(rows.iter().all(|row| row.len() == 6));
+        let levels = rows
+            .iter()
+            .map(|row| row[0].parse::<u64>().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(levels, [4, 3, 2, 1]);
+        assert_eq!(rows[3], ["1", "219", "0xdb", "12", "1752", "4096"]);
+    }
+
+    #[test]
+    fn test_format_csv_long_has_one_row_per_level_and_address() {
+        let v_addrs = [0xdead_beef.into(), 0x1337_1337.into()];
+        let csv = format_csv_long(&impls::X86_64, &v_addrs);
+        let mut lines = csv.lines();
+
+        assert_eq!(lines.next(), Some(CSV_LONG_HEADER));
+        let rows = lines.collect::<Vec<_>>();
+        assert_eq!(rows.len(), v_addrs.len() * impls::X86_64.levels as usize);
+        assert_eq!(rows[0], "0xdeadbeef,x86_64 paging,4,0,0,39,549755813888",);
+        assert_eq!(rows[3], "0xdeadbeef,x86_64 paging,1,219,1752,12,4096");
+        assert!(rows[4].starts_with("0x13371337,x86_64 paging,4,"));
+    }
+}
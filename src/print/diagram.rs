@@ -0,0 +1,88 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! ASCII diagram of the page table walk of a single address, e.g., for slides.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+
+/// Formats the page table walk for `v_addr` as a row of boxes connected by
+/// arrows: The root table comes first, followed by the tables of the lower
+/// levels, and the page with the page offset forms the last box. Each arrow
+/// is labeled with the index into the table on its left. The diagram consists
+/// of three lines of plain ASCII without ANSI escape codes.
+pub fn format_diagram(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    let lookup = paging_info.calc_page_table_lookup_meta_info(v_addr);
+    let mut labels = lookup
+        .iter()
+        .rev()
+        .map(|info| {
+            paging_info
+                .level_name(info.level)
+                .map_or_else(|| format!("level {}", info.level), ToString::to_string)
+        })
+        .collect::<Vec<_>>();
+    labels.push(format!("page +0x{:x}", paging_info.page_offset(v_addr)));
+    let arrows = lookup
+        .iter()
+        .rev()
+        .map(|info| format!("--{}-->", info.index));
+
+    let mut top = String::new();
+    let mut middle = String::new();
+    let mut bottom = String::new();
+    for (label, arrow) in labels.iter().zip(arrows.map(Some).chain([None])) {
+        let border = format!("+{}+", "-".repeat(label.len() + 2));
+        top.push_str(&border);
+        middle.push_str(&format!("| {label} |"));
+        bottom.push_str(&border);
+        if let Some(arrow) = arrow {
+            top.push_str(&" ".repeat(arrow.len()));
+            middle.push_str(&arrow);
+            bottom.push_str(&" ".repeat(arrow.len()));
+        }
+    }
+    format!("{top}\n{middle}\n{bottom}\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_format_diagram_has_one_box_per_level_and_the_page() {
+        let diagram = format_diagram(&impls::X86_64, 0xdead_beef.into());
+        let lines = diagram.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].len(), lines[1].len());
+        let boxes = lines[0].matches('+').count() / 2;
+        assert_eq!(boxes, impls::X86_64.levels as usize + 1);
+        assert_eq!(
+            lines[1],
+            "| PML4 |--0-->| PDPT |--3-->| PD |--245-->| PT |--219-->| page +0xeef |"
+        );
+    }
+}
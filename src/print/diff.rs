@@ -0,0 +1,131 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Side-by-side comparison of the page table walks of two addresses.
+
+use crate::cli::VirtualAddress;
+use crate::print::ansi_styles::{paint_highlight, paint_hint};
+use paging_calculator::addr_width::AddrWidth;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fmt::Write;
+
+/// Width of each of the two columns.
+const COLUMN_WIDTH: usize = 18;
+
+/// Formats the page table indices of both addresses side by side, from the
+/// top level to level 1, followed by the page offsets. Rows where the values
+/// differ are marked with `!=` and highlighted, matching rows are marked with
+/// `==` and muted.
+pub fn format_diff(
+    paging_info: &PagingImplInfo,
+    v_addr_a: VirtualAddress,
+    v_addr_b: VirtualAddress,
+) -> String {
+    let addr_mask = if paging_info.addr_width == AddrWidth::Bits32 {
+        0xffff_ffff
+    } else {
+        u64::MAX
+    };
+    let mut diff = String::new();
+    writeln!(
+        diff,
+        "{:22}{:>COLUMN_WIDTH$}    {:>COLUMN_WIDTH$}",
+        "",
+        format!("0x{:x}", u64::from(v_addr_a) & addr_mask),
+        format!("0x{:x}", u64::from(v_addr_b) & addr_mask),
+    )
+    .unwrap();
+
+    let mut write_row = |label: &str, a: String, b: String| {
+        let differs = a != b;
+        let row = format!(
+            "{a:>COLUMN_WIDTH$} {} {b:>COLUMN_WIDTH$}",
+            if differs { "!=" } else { "==" }
+        );
+        let row = if differs {
+            paint_highlight(&row).to_string()
+        } else {
+            paint_hint(&row).to_string()
+        };
+        writeln!(diff, "{label:<20}: {row}").unwrap();
+    };
+
+    let infos_a = paging_info.calc_page_table_lookup_meta_info(v_addr_a);
+    let infos_b = paging_info.calc_page_table_lookup_meta_info(v_addr_b);
+    for (a, b) in infos_a.iter().zip(infos_b.iter()).rev() {
+        write_row(
+            &format!("level {} entry index", a.level),
+            a.index.to_string(),
+            b.index.to_string(),
+        );
+    }
+    write_row(
+        "page offset",
+        format!("0x{:x}", paging_info.page_offset(v_addr_a)),
+        format!("0x{:x}", paging_info.page_offset(v_addr_b)),
+    );
+    diff
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_format_diff() {
+        let diff = format_diff(&impls::X86_64, 0xdead_beef.into(), 0xdead_0000.into());
+        let lines = diff.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 1 + impls::X86_64.levels as usize + 1);
+
+        // two columns: one per address
+        let header = lines[0].split_whitespace().collect::<Vec<_>>();
+        assert_eq!(header, ["0xdeadbeef", "0xdead0000"]);
+
+        let row = |level: usize| {
+            lines[1 + impls::X86_64.levels as usize - level]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(row(4)[5..], ["0", "==", "0"]);
+        assert_eq!(row(3)[5..], ["3", "==", "3"]);
+        assert_eq!(row(2)[5..], ["245", "==", "245"]);
+        assert_eq!(row(1)[5..], ["219", "!=", "208"]);
+        assert!(lines[5].starts_with("page offset"));
+        let offsets = lines[5].split_whitespace().collect::<Vec<_>>();
+        assert_eq!(offsets[3..], ["0xeef", "!=", "0x0"]);
+    }
+
+    #[test]
+    fn test_format_diff_only_level_1() {
+        let diff = format_diff(&impls::X86_64, 0x1000.into(), 0x2000.into());
+        let markers = diff
+            .lines()
+            .skip(1)
+            .map(|line| line.split_whitespace().rev().nth(1).unwrap())
+            .collect::<Vec<_>>();
+        // level 4 to level 1, followed by the page offset
+        assert_eq!(markers, ["==", "==", "==", "!=", "=="]);
+    }
+}
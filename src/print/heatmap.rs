@@ -0,0 +1,129 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Text heatmap of how often each page table index was used by a batch of
+//! addresses.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fmt::Write;
+
+/// Number of buckets the index range of each level is divided into.
+const BUCKETS: u64 = 32;
+
+/// Characters for increasing counts, relative to the maximum count of the
+/// level. An empty bucket is shown as `.`.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Returns the number of addresses whose index at the given level falls into
+/// each of the buckets. The index range of the level is divided into (at most)
+/// `buckets` buckets of equal size.
+pub fn bucket_counts(
+    paging_info: &PagingImplInfo,
+    v_addrs: &[VirtualAddress],
+    level: u64,
+    buckets: u64,
+) -> Vec<u64> {
+    let entries = 1_u128 << paging_info.index_bits_at_level(level);
+    let buckets = u128::from(buckets).min(entries);
+    let bucket_size = entries / buckets;
+    let mut counts = vec![0; buckets as usize];
+    for &v_addr in v_addrs {
+        let info = &paging_info.calc_page_table_lookup_meta_info(v_addr)[level as usize - 1];
+        counts[(u128::from(info.index) / bucket_size) as usize] += 1;
+    }
+    counts
+}
+
+/// Formats one heatmap line per level, from the top level to level 1. Each
+/// character represents a bucket of indices; the higher the block, the more
+/// addresses used an index of that bucket.
+pub fn format_heatmap(paging_info: &PagingImplInfo, v_addrs: &[VirtualAddress]) -> String {
+    let mut heatmap = String::new();
+    for level in (paging_info.leaf_level..=paging_info.levels).rev() {
+        let counts = bucket_counts(paging_info, v_addrs, level, BUCKETS);
+        let max = counts.iter().copied().max().unwrap_or(0);
+        let blocks = counts
+            .iter()
+            .map(|&count| {
+                if count == 0 {
+                    '.'
+                } else {
+                    BLOCKS[((count * BLOCKS.len() as u64 - 1) / max) as usize]
+                }
+            })
+            .collect::<String>();
+        let entries = 1_u128 << paging_info.index_bits_at_level(level);
+        writeln!(
+            heatmap,
+            "level {level} heatmap   : {blocks}  ({} indices per bucket, max {max})",
+            entries / counts.len() as u128
+        )
+        .unwrap();
+    }
+    heatmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_bucket_counts_with_64_index_bits() {
+        let info = PagingImplInfo::generic(128, 1, 64, 12, 8).unwrap();
+        let v_addrs = [
+            VirtualAddress::from(0),
+            VirtualAddress::from_u128(u128::MAX),
+        ];
+        let counts = bucket_counts(&info, &v_addrs, 1, BUCKETS);
+        assert_eq!(counts.len() as u64, BUCKETS);
+        assert_eq!(counts[0], 1);
+        assert_eq!(counts[BUCKETS as usize - 1], 1);
+        assert!(format_heatmap(&info, &v_addrs).contains("level 1 heatmap"));
+    }
+
+    #[test]
+    fn test_heatmap_concentrates_clustered_addresses() {
+        // 100 consecutive pages: level 1 indices 0..100, all other indices 0
+        let v_addrs = (0..100_u64)
+            .map(|page| VirtualAddress::from(page * 4096))
+            .collect::<Vec<_>>();
+
+        let counts = bucket_counts(&impls::X86_64, &v_addrs, 2, BUCKETS);
+        assert_eq!(counts[0], 100);
+        assert_eq!(counts.iter().sum::<u64>(), 100);
+
+        // 16 indices per bucket
+        let counts = bucket_counts(&impls::X86_64, &v_addrs, 1, BUCKETS);
+        assert_eq!(counts[..7], [16, 16, 16, 16, 16, 16, 4]);
+        assert!(counts[7..].iter().all(|&count| count == 0));
+
+        let heatmap = format_heatmap(&impls::X86_64, &v_addrs);
+        let lines = heatmap.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].starts_with("level 4 heatmap   : █..............................."));
+        assert!(lines[3].starts_with("level 1 heatmap   : ██████▂........................."));
+    }
+}
@@ -0,0 +1,157 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! JSON output of the page table lookup information.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::page_table_index::PageTableLookupMetaInfo;
+use paging_calculator::paging_info::PagingImplInfo;
+use serde::{Serialize, Serializer};
+
+/// JSON representation of the page table walk of a single address.
+#[derive(Debug, Serialize)]
+struct WalkJson<'a> {
+    /// Name of the paging implementation.
+    arch: &'a str,
+    /// The address in hexadecimal representation.
+    address: String,
+    /// The address in decimal representation.
+    address_decimal: u128,
+    /// The levels from the top level to level 1, keyed by the level number
+    /// or the name of the page table.
+    #[serde(serialize_with = "serialize_ordered_map")]
+    levels: Vec<(String, LevelJson<'a>)>,
+}
+
+/// JSON representation of a single level of a page table walk: The
+/// [`PageTableLookupMetaInfo`] plus the offset of the entry.
+#[derive(Debug, Serialize)]
+struct LevelJson<'a> {
+    /// Level, index, shift, and relevant bits of the address.
+    #[serde(flatten)]
+    info: &'a PageTableLookupMetaInfo,
+    /// Offset into the page table in bytes.
+    entry_offset: u128,
+}
+
+/// Serializes the key-value pairs as map while keeping their order.
+fn serialize_ordered_map<S: Serializer, V: Serialize>(
+    pairs: &[(String, V)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(pairs.iter().map(|(key, value)| (key, value)))
+}
+
+/// Formats the page table walk of the address as single-line JSON object. The
+/// levels are keyed by their number, or by the name of the page table (such
+/// as `PML4`) if `named_levels` is set and the name is known.
+pub fn format_json(
+    paging_info: &PagingImplInfo,
+    v_addr: VirtualAddress,
+    named_levels: bool,
+) -> String {
+    let addr = paging_info.addr_width.truncate(v_addr.as_u128());
+    let infos = paging_info.calc_page_table_lookup_meta_info(v_addr);
+    let levels = infos
+        .iter()
+        .rev()
+        .map(|info| {
+            let key = paging_info
+                .level_name(info.level)
+                .filter(|_| named_levels)
+                .map_or_else(|| info.level.to_string(), str::to_string);
+            let level = LevelJson {
+                info,
+                entry_offset: info.entry_offset(paging_info.page_table_entry_size),
+            };
+            (key, level)
+        })
+        .collect();
+    let walk = WalkJson {
+        arch: paging_info.name,
+        address: format!("0x{addr:x}"),
+        address_decimal: addr,
+        levels,
+    };
+    serde_json::to_string(&walk).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+    use serde_json::Value;
+
+    #[test]
+    fn test_format_json_numbered_levels() {
+        let json = format_json(&impls::X86_64, 0xdead_beef.into(), false);
+        let json = serde_json::from_str::<Value>(&json).unwrap();
+        assert_eq!(json["arch"], "x86_64 paging");
+        assert_eq!(json["address"], "0xdeadbeef");
+        assert_eq!(json["levels"]["3"]["index"], 3);
+        assert_eq!(json["levels"]["3"]["entry_offset"], 0x18);
+        assert_eq!(json["levels"]["1"]["index"], 219);
+    }
+
+    #[test]
+    fn test_format_json_fields() {
+        let json = format_json(&impls::X86_64, 0xdead_beef.into(), false);
+        let json = serde_json::from_str::<Value>(&json).unwrap();
+        assert_eq!(json["address_decimal"], 0xdead_beef_u64);
+
+        let indices = ["4", "3", "2", "1"]
+            .iter()
+            .map(|&level| json["levels"][level]["index"].as_u64().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(indices, [0, 3, 245, 219]);
+
+        let level_2 = &json["levels"]["2"];
+        assert_eq!(level_2["level"], 2);
+        assert_eq!(level_2["shift"], 21);
+        assert_eq!(level_2["entry_offset"], 245 * 8);
+        assert_eq!(level_2["relevant_bits"], 0x1ea0_0000);
+        assert_eq!(level_2.get("v_addr"), None);
+    }
+
+    #[test]
+    fn test_format_json_with_64_index_bits() {
+        let info = PagingImplInfo::generic(128, 1, 64, 12, 8).unwrap();
+        let json = format_json(
+            &info,
+            VirtualAddress::from_u128(0xfff_ffff_ffff_ffff_f000),
+            false,
+        );
+        assert!(json.contains("\"entry_offset\":147573952589676412920"));
+    }
+
+    #[test]
+    fn test_format_json_named_levels() {
+        let json = format_json(&impls::X86_64, 0xdead_beef.into(), true);
+        assert!(json.contains("\"levels\":{\"PML4\":"));
+        let json = serde_json::from_str::<Value>(&json).unwrap();
+        assert_eq!(json["levels"]["PML4"]["index"], 0);
+        assert_eq!(json["levels"]["PDPT"]["index"], 3);
+        assert_eq!(json["levels"]["PT"]["index"], 219);
+        assert_eq!(json["levels"].get("4"), None);
+    }
+}
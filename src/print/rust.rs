@@ -0,0 +1,122 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Rust source output of the page table lookup information, for embedding
+//! precomputed expectations into tests.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fmt::Write;
+
+/// Formats the page table walk of the address as Rust `const` array of
+/// `(level, index, offset)` tuples, ordered from the top level to level 1.
+pub fn format_rust(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    let addr = paging_info.addr_width.truncate(v_addr.as_u128());
+    // Offsets into tables with 64 index bits don't fit into a `u64`.
+    let offset_type = if paging_info.table_size_bytes() > u128::from(u64::MAX) {
+        "u128"
+    } else {
+        "u64"
+    };
+    let mut rust = String::new();
+    writeln!(
+        rust,
+        "/// {}: page table walk of 0x{addr:x} as (level, index, offset) tuples.",
+        paging_info.name
+    )
+    .unwrap();
+    writeln!(
+        rust,
+        "const WALK_0X{addr:X}: [(u64, u64, {offset_type}); {}] = [",
+        paging_info.walked_levels()
+    )
+    .unwrap();
+    for info in paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+    {
+        writeln!(
+            rust,
+            "    ({}, {}, 0x{:x}),",
+            info.level,
+            info.index,
+            info.entry_offset(paging_info.page_table_entry_size)
+        )
+        .unwrap();
+    }
+    writeln!(rust, "];").unwrap();
+    rust
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_format_rust() {
+        let rust = format_rust(&impls::X86_64, 0xdead_beef.into());
+        let lines = rust.lines().collect::<Vec<_>>();
+
+        assert!(lines[0].starts_with("/// x86_64 paging"));
+        assert_eq!(lines[1], "const WALK_0XDEADBEEF: [(u64, u64, u64); 4] = [");
+        let tuples = &lines[2..lines.len() - 1];
+        assert_eq!(tuples.len(), impls::X86_64.levels as usize);
+        assert!(tuples
+            .iter()
+            .all(|t| t.trim().starts_with('(') && t.ends_with("),") && t.split(", ").count() == 3));
+        assert_eq!(tuples[0], "    (4, 0, 0x0),");
+        assert_eq!(tuples[3], "    (1, 219, 0x6d8),");
+        assert_eq!(lines.last(), Some(&"];"));
+    }
+
+    #[test]
+    fn test_format_rust_with_huge_page() {
+        let info = impls::X86_64.with_page_size(2 * 1024 * 1024).unwrap();
+        let rust = format_rust(&info, 0xdead_beef.into());
+        let lines = rust.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[1], "const WALK_0XDEADBEEF: [(u64, u64, u64); 3] = [");
+        let tuples = &lines[2..lines.len() - 1];
+        assert_eq!(tuples.len(), 3);
+        assert_eq!(tuples[2], "    (2, 245, 0x7a8),");
+    }
+
+    #[test]
+    fn test_format_rust_with_64_index_bits() {
+        let info = PagingImplInfo::generic(128, 1, 64, 12, 8).unwrap();
+        let rust = format_rust(&info, VirtualAddress::from_u128(0xfff_ffff_ffff_ffff_f000));
+        let lines = rust.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[1],
+            "const WALK_0XFFFFFFFFFFFFFFFF000: [(u64, u64, u128); 1] = ["
+        );
+        assert_eq!(
+            lines[2],
+            "    (1, 18446744073709551615, 0x7fffffffffffffff8),"
+        );
+    }
+}
@@ -0,0 +1,92 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! S-expression output of the page table lookup information, for Lisp- or
+//! Scheme-based tooling.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fmt::Write;
+
+/// Formats the page table walk of the address as a single S-expression, such
+/// as `(walk (arch "x86_64 paging") (addr #xdeadbeef) (level 4 (index 0)
+/// (offset #x0)) ... (page-offset #xeef))`. The levels are ordered from the
+/// top level to level 1. Addresses and offsets use the `#x` hex syntax.
+pub fn format_sexp(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    let addr = paging_info.addr_width.truncate(v_addr.as_u128());
+    let mut sexp = String::new();
+    write!(
+        sexp,
+        "(walk (arch \"{}\") (addr #x{addr:x})",
+        paging_info.name
+    )
+    .unwrap();
+    for info in paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+    {
+        write!(
+            sexp,
+            " (level {} (index {}) (offset #x{:x}))",
+            info.level,
+            info.index,
+            info.entry_offset(paging_info.page_table_entry_size)
+        )
+        .unwrap();
+    }
+    write!(
+        sexp,
+        " (page-offset #x{:x}))",
+        paging_info.page_offset(v_addr)
+    )
+    .unwrap();
+    sexp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_format_sexp() {
+        let sexp = format_sexp(&impls::X86_64, 0xdead_beef.into());
+        assert!(sexp.starts_with("(walk (arch \"x86_64 paging\") (addr #xdeadbeef) (level 4"));
+
+        // balanced parentheses that never close more than were opened
+        let depth = sexp.chars().try_fold(0_i32, |depth, c| {
+            let depth = match c {
+                '(' => depth + 1,
+                ')' => depth - 1,
+                _ => depth,
+            };
+            (depth >= 0).then_some(depth)
+        });
+        assert_eq!(depth, Some(0));
+
+        assert_eq!(sexp.matches("(level ").count(), 4);
+        assert!(sexp.contains("(level 1 (index 219) (offset #x6d8))"));
+    }
+}
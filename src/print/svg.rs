@@ -0,0 +1,145 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! SVG output of the page table lookup information: The translated bits of
+//! the address as a bar with one colored and labeled segment per field.
+
+use crate::cli::VirtualAddress;
+use crate::print::format_bit_range;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fmt::Write;
+
+/// Width of a single bit in the bar.
+const BIT_WIDTH: u64 = 16;
+/// Height of the bar.
+const BAR_HEIGHT: u64 = 48;
+/// Fill colors of the levels, starting with level 1. The page offset uses the
+/// last color.
+const COLORS: [&str; 6] = [
+    "#8dd3c7", "#ffffb3", "#bebada", "#fb8072", "#80b1d3", "#d9d9d9",
+];
+
+/// Renders the address-field breakdown of the address as self-contained SVG.
+/// Each page table level and the page offset form one segment, whose width is
+/// proportional to its number of bits. Each segment is labeled with the name
+/// of the field, its bit range, and its value.
+pub fn format_svg(paging_info: &PagingImplInfo, v_addr: VirtualAddress) -> String {
+    let translated_bits = paging_info.translated_bits();
+    let width = translated_bits * BIT_WIDTH;
+
+    let mut fields = paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+        .map(|info| {
+            let name = paging_info
+                .level_name(info.level)
+                .map_or_else(|| format!("level {}", info.level), ToString::to_string);
+            let color = COLORS[(info.level as usize - 1) % (COLORS.len() - 1)];
+            (
+                name,
+                paging_info.bit_range(info.level),
+                info.index.to_string(),
+                color,
+            )
+        })
+        .collect::<Vec<_>>();
+    fields.push((
+        "offset".to_string(),
+        paging_info.page_offset_bit_range(),
+        format!("0x{:x}", paging_info.page_offset(v_addr)),
+        COLORS[COLORS.len() - 1],
+    ));
+
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{BAR_HEIGHT}" viewBox="0 0 {width} {BAR_HEIGHT}" font-family="monospace" font-size="11" text-anchor="middle">"#
+    )
+    .unwrap();
+    for (name, bits, value, color) in fields {
+        let x = (translated_bits - 1 - bits.end()) * BIT_WIDTH;
+        let segment_width = (bits.end() - bits.start() + 1) * BIT_WIDTH;
+        let center = x + segment_width / 2;
+        writeln!(svg, "  <g>").unwrap();
+        writeln!(
+            svg,
+            r#"    <rect x="{x}" y="0" width="{segment_width}" height="{BAR_HEIGHT}" fill="{color}" stroke="black"/>"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"    <text x="{center}" y="16">{name} {}</text>"#,
+            format_bit_range(&bits)
+        )
+        .unwrap();
+        writeln!(svg, r#"    <text x="{center}" y="36">{value}</text>"#).unwrap();
+        writeln!(svg, "  </g>").unwrap();
+    }
+    writeln!(svg, "</svg>").unwrap();
+    svg
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    /// Checks that all tags are properly nested and closed.
+    fn assert_well_formed(xml: &str) {
+        let mut open_tags = vec![];
+        for tag in xml.split('<').skip(1) {
+            let tag = &tag[..tag.find('>').unwrap()];
+            if let Some(name) = tag.strip_prefix('/') {
+                assert_eq!(open_tags.pop(), Some(name));
+            } else if !tag.ends_with('/') {
+                open_tags.push(tag.split_whitespace().next().unwrap());
+            }
+        }
+        assert!(open_tags.is_empty());
+    }
+
+    #[test]
+    fn test_format_svg() {
+        let svg = format_svg(&impls::X86_64, 0xdead_beef.into());
+        assert_well_formed(&svg);
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+
+        // one segment per level plus the page offset
+        let fields = impls::X86_64.levels as usize + 1;
+        assert_eq!(svg.matches("<rect").count(), fields);
+        assert_eq!(svg.matches("<g>").count(), fields);
+        for label in [
+            "PML4 [47:39]",
+            "PDPT [38:30]",
+            "PD [29:21]",
+            "PT [20:12]",
+            "offset [11:0]",
+        ] {
+            assert!(svg.contains(label), "{label}");
+        }
+        assert!(svg.contains(">219</text>"));
+        assert!(svg.contains(">0xeef</text>"));
+    }
+}
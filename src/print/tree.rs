@@ -0,0 +1,93 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Indented tree output of the page table walk of a single address.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::fmt::Write;
+
+/// Indentation per level of the tree.
+const INDENT: &str = "  ";
+
+/// Formats the single path through the page table hierarchy that the walk for
+/// `v_addr` takes as indented outline: The root table comes first, each
+/// following level is indented one step further, and the page offset forms
+/// the leaf, named by `offset_label`.
+pub fn format_tree(
+    paging_info: &PagingImplInfo,
+    v_addr: VirtualAddress,
+    offset_label: &str,
+) -> String {
+    let mut tree = String::new();
+    let mut depth = 0;
+    for info in paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+    {
+        let table = paging_info
+            .level_name(info.level)
+            .map_or_else(|| format!("level {}", info.level), ToString::to_string);
+        writeln!(
+            tree,
+            "{indent}{table}: index {index}, entry offset 0x{offset:04x}",
+            indent = INDENT.repeat(depth),
+            index = info.index,
+            offset = info.entry_offset(paging_info.page_table_entry_size),
+        )
+        .unwrap();
+        depth += 1;
+    }
+    writeln!(
+        tree,
+        "{indent}{offset_label}: 0x{offset:x}",
+        indent = INDENT.repeat(depth),
+        offset = paging_info.page_offset(v_addr),
+    )
+    .unwrap();
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    #[test]
+    fn test_format_tree_has_one_line_per_level_and_increasing_indentation() {
+        let tree = format_tree(&impls::X86_64, 0xdead_beef.into(), "page offset");
+        let lines = tree.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines.len(), impls::X86_64.levels as usize + 1);
+        let indentations = lines
+            .iter()
+            .map(|line| line.len() - line.trim_start().len())
+            .collect::<Vec<_>>();
+        assert!(indentations.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(lines[0], "PML4: index 0, entry offset 0x0000");
+        assert_eq!(lines[3], "      PT: index 219, entry offset 0x06d8");
+        assert_eq!(lines[4], "        page offset: 0xeef");
+    }
+}
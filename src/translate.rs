@@ -0,0 +1,309 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module to translate a virtual address to a physical address by walking
+//! the page tables of a physical memory image.
+
+use crate::cli::VirtualAddress;
+use paging_calculator::paging_info::PagingImplInfo;
+use std::error::Error;
+
+/// The present bit of a page table entry.
+const PRESENT_BIT: u64 = 1 << 0;
+
+/// The page size bit of a page table entry, which marks an entry that maps a
+/// huge page instead of referencing a next-level page table.
+const PAGE_SIZE_BIT: u64 = 1 << 7;
+
+/// Describes why a translation stopped without resolving the address.
+#[derive(Copy, Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum TranslateError {
+    /// The entry is outside of the memory image.
+    #[display("The level {level} entry at 0x{entry_addr:x} is outside of the memory image.")]
+    OutOfBounds {
+        /// Level of the entry.
+        level: u64,
+        /// Physical address of the entry. This is a `u128`, as the address
+        /// may exceed the 64-bit physical address space.
+        entry_addr: u128,
+    },
+    /// The present bit of the entry is not set.
+    #[display("The level {level} entry 0x{entry:x} at 0x{entry_addr:x} is not present.")]
+    NotPresent {
+        /// Level of the entry.
+        level: u64,
+        /// Physical address of the entry.
+        entry_addr: u64,
+        /// The raw entry.
+        entry: u64,
+    },
+}
+
+impl Error for TranslateError {}
+
+/// A page table entry that was read during a [`Translation`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TranslationStep {
+    /// Level of the entry.
+    pub level: u64,
+    /// Physical address of the entry.
+    pub entry_addr: u64,
+    /// The raw entry.
+    pub entry: u64,
+}
+
+/// The result of walking the page tables of a memory image.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Translation {
+    /// The entries from the top level to the leaf level.
+    pub steps: Vec<TranslationStep>,
+    /// The level of the entry that maps the page. Bigger than 1 if the walk
+    /// stopped at a huge page.
+    pub leaf_level: u64,
+    /// The resolved physical address.
+    pub phys_addr: u64,
+}
+
+/// Walks the page tables in the memory image, whose first byte is at physical
+/// address zero, starting with the root page table at the given physical
+/// address. Entries are read in little-endian with the entry size of the
+/// paging implementation.
+///
+/// The walk stops at the first entry whose present bit is clear and reports
+/// it as error. It also stops at an intermediate level if the page size bit
+/// marks a huge page; the physical address is then resolved within the huge
+/// page.
+pub fn translate(
+    image: &[u8],
+    root: u64,
+    paging_info: &PagingImplInfo,
+    v_addr: VirtualAddress,
+) -> Result<Translation, TranslateError> {
+    let entry_size = paging_info.page_table_entry_size;
+    let mut table = root;
+    let mut steps = vec![];
+    for info in paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+    {
+        let level = info.level;
+        let Some((entry_addr, entry)) = info
+            .index
+            .checked_mul(entry_size)
+            .and_then(|offset| table.checked_add(offset))
+            .and_then(|entry_addr| Some((entry_addr, read_entry(image, entry_addr, entry_size)?)))
+        else {
+            return Err(TranslateError::OutOfBounds {
+                level,
+                entry_addr: u128::from(table) + info.entry_offset(entry_size),
+            });
+        };
+        steps.push(TranslationStep {
+            level,
+            entry_addr,
+            entry,
+        });
+        if entry & PRESENT_BIT == 0 {
+            return Err(TranslateError::NotPresent {
+                level,
+                entry_addr,
+                entry,
+            });
+        }
+        let is_huge_page = level > paging_info.leaf_level
+            && paging_info.huge_page_levels.contains(&level)
+            && entry & PAGE_SIZE_BIT != 0;
+        if level == paging_info.leaf_level || is_huge_page {
+            let offset = u64::from(v_addr) & (paging_info.coverage_per_entry(level) - 1);
+            return Ok(Translation {
+                steps,
+                leaf_level: level,
+                phys_addr: paging_info.entry_frame_base(entry, level) + offset,
+            });
+        }
+        table = paging_info.entry_frame_base(entry, 1);
+    }
+    unreachable!("the walk always ends at the leaf level");
+}
+
+/// Returns the virtual address at which the page table in the physical frame
+/// is accessible through the recursive self-map at the root table entry
+/// `slot`, together with the level of the page table.
+///
+/// The frame must hold one of the page tables that the walk of `v_addr` reads
+/// from the memory image, starting with the root page table at the given
+/// physical address. Returns `None` otherwise, i.e., if the walk ends before
+/// it reaches the frame.
+pub fn selfmap_frame_address(
+    image: &[u8],
+    root: u64,
+    paging_info: &PagingImplInfo,
+    v_addr: VirtualAddress,
+    slot: u64,
+    frame: u64,
+) -> Option<(u64, u64)> {
+    let entry_size = paging_info.page_table_entry_size;
+    let mut table = root;
+    for info in paging_info
+        .calc_page_table_lookup_meta_info(v_addr)
+        .iter()
+        .rev()
+    {
+        let level = info.level;
+        if table == frame {
+            let addr = paging_info.recursive_table_address(slot, v_addr, level);
+            return Some((addr, level));
+        }
+        let entry_addr = table.checked_add(info.index.checked_mul(entry_size)?)?;
+        let entry = read_entry(image, entry_addr, entry_size)?;
+        let is_huge_page =
+            paging_info.huge_page_levels.contains(&level) && entry & PAGE_SIZE_BIT != 0;
+        if entry & PRESENT_BIT == 0 || level == paging_info.leaf_level || is_huge_page {
+            return None;
+        }
+        table = paging_info.entry_frame_base(entry, 1);
+    }
+    None
+}
+
+/// Reads the little-endian entry of the given size at the physical address of
+/// the memory image. Returns `None` if the entry is outside of the image.
+fn read_entry(image: &[u8], addr: u64, size: u64) -> Option<u64> {
+    let start = usize::try_from(addr).ok()?;
+    let bytes = image.get(start..start.checked_add(size as usize)?)?;
+    Some(
+        bytes
+            .iter()
+            .rev()
+            .fold(0, |entry, &byte| entry << 8 | u64::from(byte)),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use paging_calculator::paging_info::impls;
+
+    /// Creates a memory image with a 4-level x86_64 page table hierarchy with
+    /// the root at `0x1000` that maps the page at virtual address `0x1000` to
+    /// the frame at `0x5000`. Further entries can be written with `write`.
+    fn memory_image() -> Vec<u8> {
+        let mut image = vec![0; 0x6000];
+        write(&mut image, 0x1000, 0x2003); // PML4[0] -> PDPT
+        write(&mut image, 0x2000, 0x3003); // PDPT[0] -> PD
+        write(&mut image, 0x3000, 0x4003); // PD[0] -> PT
+        write(&mut image, 0x4008, 0x5003); // PT[1] -> frame
+        image
+    }
+
+    fn write(image: &mut [u8], addr: usize, entry: u64) {
+        image[addr..addr + 8].copy_from_slice(&entry.to_le_bytes());
+    }
+
+    #[test]
+    fn test_translate() {
+        let image = memory_image();
+        let translation = translate(&image, 0x1000, &impls::X86_64, 0x1234.into()).unwrap();
+        assert_eq!(translation.phys_addr, 0x5234);
+        assert_eq!(translation.leaf_level, 1);
+        assert_eq!(
+            translation
+                .steps
+                .iter()
+                .map(|step| step.entry_addr)
+                .collect::<Vec<_>>(),
+            [0x1000, 0x2000, 0x3000, 0x4008]
+        );
+    }
+
+    #[test]
+    fn test_translate_not_present() {
+        let mut image = memory_image();
+        write(&mut image, 0x4008, 0x5002);
+        assert_eq!(
+            translate(&image, 0x1000, &impls::X86_64, 0x1234.into()),
+            Err(TranslateError::NotPresent {
+                level: 1,
+                entry_addr: 0x4008,
+                entry: 0x5002
+            })
+        );
+        // PT[2] was never written
+        assert!(matches!(
+            translate(&image, 0x1000, &impls::X86_64, 0x2000.into()),
+            Err(TranslateError::NotPresent { level: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_translate_huge_page() {
+        let mut image = memory_image();
+        write(&mut image, 0x3000, 0x20_0083); // PD[0] -> 2 MiB page
+        let translation = translate(&image, 0x1000, &impls::X86_64, 0x1234.into()).unwrap();
+        assert_eq!(translation.leaf_level, 2);
+        assert_eq!(translation.phys_addr, 0x20_1234);
+        assert_eq!(translation.steps.len(), 3);
+    }
+
+    #[test]
+    fn test_selfmap_frame_address() {
+        let image = memory_image();
+        let selfmap = |frame| {
+            selfmap_frame_address(&image, 0x1000, &impls::X86_64, 0x1234.into(), 510, frame)
+        };
+        // the PT of the walk is reached by looping once through slot 510
+        assert_eq!(selfmap(0x4000), Some((0xffff_ff00_0000_0000, 1)));
+        assert_eq!(selfmap(0x3000), Some((0xffff_ff7f_8000_0000, 2)));
+        // the root table is reached by looping through slot 510 at all levels
+        assert_eq!(selfmap(0x1000), Some((0xffff_ff7f_bfdf_e000, 4)));
+        // the frame of the page isn't a page table
+        assert_eq!(selfmap(0x5000), None);
+    }
+
+    #[test]
+    fn test_translate_out_of_bounds() {
+        let image = memory_image();
+        assert_eq!(
+            translate(&image, 0x8000, &impls::X86_64, 0x1234.into()),
+            Err(TranslateError::OutOfBounds {
+                level: 4,
+                entry_addr: 0x8000
+            })
+        );
+        // the entry address exceeds the 64-bit physical address space
+        assert_eq!(
+            translate(
+                &image,
+                0xffff_ffff_ffff_fff8,
+                &impls::X86_64,
+                0x7f80_0000_0000.into()
+            ),
+            Err(TranslateError::OutOfBounds {
+                level: 4,
+                entry_addr: 0x1_0000_0000_0000_07f0
+            })
+        );
+    }
+}
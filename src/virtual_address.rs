@@ -0,0 +1,390 @@
+/*
+MIT License
+
+Copyright (c) 2024 Philipp Schuster
+
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Module for [`VirtualAddress`].
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+/// A virtual address within the range of `u128`.
+///
+/// It can be provided to the CLI in hexadecimal as `0x123` or `0x1234_5678`,
+/// in binary as `0b1010`, or in decimal as `4096`, i.e., without a prefix.
+/// Decimal values may have a binary size suffix `k`, `m`, `g`, or `t`, such as
+/// `2m` for `0x200000`. A power of two can be written as `2^N` or `1<<N` with
+/// `N < 64`, such as `2^21` for `0x200000`. Addresses wider than 64 bits are
+/// only meaningful for custom paging implementations with 128-bit addresses.
+/// Can be truncated to `u64` or `u32`. In this case, the upper bits are
+/// discarded.
+#[derive(Copy, Clone, Debug, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub struct VirtualAddress(u128);
+
+impl fmt::Display for VirtualAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "0x{:016x}", self.0)
+    }
+}
+
+impl VirtualAddress {
+    /// Creates a virtual address from a value that may be wider than 64 bits.
+    pub const fn from_u128(addr: u128) -> Self {
+        Self(addr)
+    }
+
+    /// Returns the full value of the virtual address, including the bits
+    /// above 64 bits.
+    pub const fn as_u128(self) -> u128 {
+        self.0
+    }
+
+    /// Adds two addresses, such as a base and an offset. Returns `None` on
+    /// overflow.
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(addr) => Some(Self(addr)),
+            None => None,
+        }
+    }
+
+    /// Prefix of the hexadecimal representation.
+    pub const HEX_PREFIX: &'static str = "0x";
+    /// Prefix of the binary representation.
+    pub const BIN_PREFIX: &'static str = "0b";
+    /// Prefixes of a power of two that is followed by the decimal exponent.
+    const POWER_PREFIXES: [&'static str; 2] = ["2^", "1<<"];
+    /// Size suffixes of decimal values and their multipliers.
+    const SIZE_SUFFIXES: [(char, u128); 4] = [
+        ('k', 1 << 10),
+        ('m', 1 << 20),
+        ('g', 1 << 30),
+        ('t', 1 << 40),
+    ];
+}
+
+/// Radix of a number without a prefix such as `0x`.
+///
+/// Numbers with a prefix always use the radix of the prefix. As `b` is a
+/// hexadecimal digit, the prefix `0b` is not recognized with [`Radix::Hex`].
+/// See [`VirtualAddress::parse`].
+#[derive(Copy, Clone, Debug, Default, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum Radix {
+    /// Decimal, unless there is a prefix. This is how [`VirtualAddress`]
+    /// implements [`FromStr`].
+    #[default]
+    Auto,
+    /// Hexadecimal, such as `deadbeef`. Also, `0b1000` is `0xb1000`.
+    Hex,
+    /// Decimal, such as `4096`.
+    Dec,
+    /// Binary, such as `1000`.
+    Bin,
+}
+
+impl Radix {
+    /// Returns the radix of a number without a prefix.
+    const fn of_bare_number(self) -> u32 {
+        match self {
+            Self::Auto | Self::Dec => 10,
+            Self::Hex => 16,
+            Self::Bin => 2,
+        }
+    }
+}
+
+impl FromStr for Radix {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(Self::Auto),
+            "hex" => Ok(Self::Hex),
+            "dec" => Ok(Self::Dec),
+            "bin" => Ok(Self::Bin),
+            _ => Err(format!("Unknown radix `{s}`.")),
+        }
+    }
+}
+
+/// Describes errors that happened when users tries to input a [`VirtualAddress`]
+/// via the CLI.
+#[derive(Copy, Clone, Debug, derive_more::Display, PartialOrd, PartialEq, Ord, Eq, Hash)]
+pub enum VirtualAddressError {
+    /// The virtual address could not be parsed as number as `u128`
+    #[display("The virtual address could not be parsed as number as `u128`.")]
+    ParseIntError,
+    /// A size suffix such as `m` was used with a hexadecimal or binary value.
+    #[display("Size suffixes such as `2m` are only allowed for decimal values.")]
+    AmbiguousSuffix,
+    /// The exponent of a power of two such as `2^N` is not below 64.
+    #[display("The exponent of a power of two such as `2^N` must be below 64.")]
+    ExponentTooLarge,
+}
+
+impl Error for VirtualAddressError {}
+
+impl From<u64> for VirtualAddress {
+    fn from(value: u64) -> Self {
+        Self(value.into())
+    }
+}
+
+impl From<VirtualAddress> for u64 {
+    fn from(value: VirtualAddress) -> Self {
+        value.0 as Self
+    }
+}
+
+impl From<VirtualAddress> for u32 {
+    fn from(value: VirtualAddress) -> Self {
+        value.0 as Self
+    }
+}
+
+impl FromStr for VirtualAddress {
+    type Err = VirtualAddressError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, Radix::Auto)
+    }
+}
+
+impl VirtualAddress {
+    /// Parses a virtual address like [`FromStr`], but interprets a number
+    /// without a prefix in the given radix. For example, `deadbeef` is only
+    /// valid with [`Radix::Hex`]. Size suffixes such as `2m` are only allowed
+    /// for decimal numbers.
+    pub fn parse(s: &str, radix: Radix) -> Result<Self, VirtualAddressError> {
+        // Remove underscores and other clutter which are allowed for the input.
+        let s = s.trim().to_lowercase().replace('_', "");
+
+        // A power of two such as `2^21` or `1 << 30`.
+        let without_spaces = s.replace(' ', "");
+        let power = Self::POWER_PREFIXES
+            .iter()
+            .find_map(|prefix| without_spaces.strip_prefix(prefix));
+        if let Some(exponent) = power {
+            return match exponent.parse::<u32>() {
+                Ok(exponent) if exponent < 64 => Ok(Self(1 << exponent)),
+                Ok(_) => Err(VirtualAddressError::ExponentTooLarge),
+                Err(_) => Err(VirtualAddressError::ParseIntError),
+            };
+        }
+
+        // The prefix determines the radix. Without a prefix, it's decimal.
+        // In hexadecimal, `0b` are regular digits and not a prefix.
+        let prefixes: &[_] = if radix == Radix::Hex {
+            &[(Self::HEX_PREFIX, 16)]
+        } else {
+            &[(Self::HEX_PREFIX, 16), (Self::BIN_PREFIX, 2)]
+        };
+        let (digits, radix) = prefixes
+            .iter()
+            .find_map(|&(prefix, radix)| s.strip_prefix(prefix).map(|digits| (digits, radix)))
+            .unwrap_or_else(|| (s.as_str(), radix.of_bare_number()));
+
+        let suffix = Self::SIZE_SUFFIXES
+            .iter()
+            .find_map(|&(suffix, multiplier)| {
+                digits
+                    .strip_suffix(suffix)
+                    .map(|digits| (digits, multiplier))
+            });
+        let (digits, multiplier) = match suffix {
+            Some(_) if radix != 10 => return Err(VirtualAddressError::AmbiguousSuffix),
+            Some(suffix) => suffix,
+            None => (digits, 1),
+        };
+
+        u128::from_str_radix(digits, radix)
+            .ok()
+            .and_then(|value| value.checked_mul(multiplier))
+            .map(Self)
+            .ok_or(VirtualAddressError::ParseIntError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_virtual_addr_from_str() {
+        assert_eq!(VirtualAddress::from_str("0x123"), Ok(0x123.into()));
+        assert_eq!(
+            VirtualAddress::from_str("0xdead_beef"),
+            Ok(0xdead_beef.into())
+        );
+        assert_eq!(
+            VirtualAddress::from_str("    0xdEAd_bEEF    "),
+            Ok(0xdead_beef.into())
+        );
+    }
+
+    #[test]
+    fn test_virtual_addr_from_str_radix() {
+        assert_eq!(VirtualAddress::from_str("4096"), Ok(0x1000.into()));
+        assert_eq!(VirtualAddress::from_str("4_096"), Ok(0x1000.into()));
+        assert_eq!(
+            VirtualAddress::from_str("0b1000000000000"),
+            Ok(0x1000.into())
+        );
+        assert_eq!(
+            VirtualAddress::from_str("0xdead_beef"),
+            VirtualAddress::from_str("3735928559")
+        );
+        assert_eq!(
+            VirtualAddress::from_str("dead_beef"),
+            Err(VirtualAddressError::ParseIntError)
+        );
+        assert_eq!(
+            VirtualAddress::from_str("0b102"),
+            Err(VirtualAddressError::ParseIntError)
+        );
+    }
+
+    #[test]
+    fn test_virtual_addr_from_str_size_suffix() {
+        assert_eq!(VirtualAddress::from_str("4k"), Ok(0x1000.into()));
+        assert_eq!(VirtualAddress::from_str("2m"), Ok(0x20_0000.into()));
+        assert_eq!(VirtualAddress::from_str("2M"), Ok(0x20_0000.into()));
+        assert_eq!(VirtualAddress::from_str("1g"), Ok(0x4000_0000.into()));
+        assert_eq!(VirtualAddress::from_str("1t"), Ok(0x100_0000_0000.into()));
+        assert_eq!(
+            VirtualAddress::from_str("0x10m"),
+            Err(VirtualAddressError::AmbiguousSuffix)
+        );
+        assert_eq!(
+            VirtualAddress::from_str("0b1k"),
+            Err(VirtualAddressError::AmbiguousSuffix)
+        );
+        assert_eq!(
+            VirtualAddress::from_str("m"),
+            Err(VirtualAddressError::ParseIntError)
+        );
+        assert_eq!(
+            VirtualAddress::from_str("1000000000000000000000000000000t"),
+            Err(VirtualAddressError::ParseIntError)
+        );
+    }
+
+    #[test]
+    fn test_virtual_addr_from_str_power_of_two() {
+        assert_eq!(VirtualAddress::from_str("2^21"), Ok(0x20_0000.into()));
+        assert_eq!(VirtualAddress::from_str("1<<30"), Ok(0x4000_0000.into()));
+        assert_eq!(VirtualAddress::from_str("1 << 30"), Ok(0x4000_0000.into()));
+        assert_eq!(VirtualAddress::from_str("2^0"), Ok(0x1.into()));
+        assert_eq!(VirtualAddress::from_str("2^63"), Ok((1 << 63).into()));
+        assert_eq!(
+            VirtualAddress::from_str("2^64"),
+            Err(VirtualAddressError::ExponentTooLarge)
+        );
+        assert_eq!(
+            VirtualAddress::from_str("1<<0x10"),
+            Err(VirtualAddressError::ParseIntError)
+        );
+        assert_eq!(
+            VirtualAddress::from_str("2^"),
+            Err(VirtualAddressError::ParseIntError)
+        );
+    }
+
+    #[test]
+    fn test_virtual_addr_parse_radix() {
+        assert_eq!(
+            VirtualAddress::parse("deadbeef", Radix::Hex),
+            Ok(0xdead_beef.into())
+        );
+        assert_eq!(
+            VirtualAddress::parse("dead_BEEF", Radix::Hex),
+            Ok(0xdead_beef.into())
+        );
+        assert_eq!(VirtualAddress::parse("1000", Radix::Hex), Ok(0x1000.into()));
+        assert_eq!(VirtualAddress::parse("1000", Radix::Dec), Ok(1000.into()));
+        assert_eq!(VirtualAddress::parse("1000", Radix::Auto), Ok(1000.into()));
+        assert_eq!(VirtualAddress::parse("1000", Radix::Bin), Ok(0b1000.into()));
+        // a prefix takes precedence over the radix
+        for radix in [Radix::Auto, Radix::Hex, Radix::Dec, Radix::Bin] {
+            assert_eq!(VirtualAddress::parse("0x10", radix), Ok(0x10.into()));
+        }
+        for radix in [Radix::Auto, Radix::Dec, Radix::Bin] {
+            assert_eq!(VirtualAddress::parse("0b10", radix), Ok(0b10.into()));
+        }
+        // ... except for `0b`, which are hexadecimal digits
+        assert_eq!(
+            VirtualAddress::parse("0b1000", Radix::Hex),
+            Ok(0xb1000.into())
+        );
+        assert_eq!(VirtualAddress::parse("0bad", Radix::Hex), Ok(0xbad.into()));
+        assert_eq!(
+            VirtualAddress::parse("deadbeef", Radix::Auto),
+            Err(VirtualAddressError::ParseIntError)
+        );
+        assert_eq!(
+            VirtualAddress::parse("deadbeef", Radix::Dec),
+            Err(VirtualAddressError::ParseIntError)
+        );
+        assert_eq!(
+            VirtualAddress::parse("102", Radix::Bin),
+            Err(VirtualAddressError::ParseIntError)
+        );
+        assert_eq!(
+            VirtualAddress::parse("2m", Radix::Hex),
+            Err(VirtualAddressError::AmbiguousSuffix)
+        );
+        assert_eq!(Radix::from_str("hex"), Ok(Radix::Hex));
+        assert!(Radix::from_str("oct").is_err());
+    }
+
+    #[test]
+    fn test_virtual_addr_64_to_32_bit() {
+        let v_addr = VirtualAddress::from_str("0xdead_beef_1337_1337");
+        assert_eq!(v_addr, Ok(0xdead_beef_1337_1337.into()));
+        let v_addr = v_addr.unwrap();
+        assert_eq!(u32::from(v_addr), 0x1337_1337);
+    }
+
+    #[test]
+    fn test_virtual_addr_checked_add() {
+        let base = VirtualAddress::from(0x1000);
+        assert_eq!(base.checked_add(0x20.into()), Some(0x1020.into()));
+        assert_eq!(
+            VirtualAddress::from_u128(u128::MAX).checked_add(1.into()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_virtual_addr_128_bit() {
+        let v_addr = VirtualAddress::from_str("0x1_0000_0000_0000_1234").unwrap();
+        assert_eq!(v_addr.as_u128(), 0x1_0000_0000_0000_1234);
+        assert_eq!(v_addr, VirtualAddress::from_u128(1 << 64 | 0x1234));
+        assert_eq!(u64::from(v_addr), 0x1234);
+        assert_eq!(v_addr.to_string(), "0x10000000000001234");
+        assert_eq!(
+            VirtualAddress::from_str("0x1_0000_0000_0000_0000_0000_0000_0000_0000"),
+            Err(VirtualAddressError::ParseIntError)
+        );
+    }
+}
@@ -17,6 +17,36 @@ function fn_main() {
 
     test_cmd "test_res/0xdeadbeef_x86_64_5level.stdout.txt" \
         "cargo run 2>/dev/null -- 0xdeadbeef x86_64 --five-level"
+
+    test_cmd "test_res/0xdeadbeef_x86_brief.stdout.txt" \
+        "cargo run 2>/dev/null -- 0xdeadbeef x86 --brief"
+
+    test_cmd "test_res/0xdeadbeef_x86_64_bottom_up.stdout.txt" \
+        "cargo run 2>/dev/null -- 0xdeadbeef x86_64 --order bottom-up"
+
+    test_cmd "test_res/0xdeadbeef_x86_64_quiet.stdout.txt" \
+        "cargo run 2>/dev/null -- 0xdeadbeef x86_64 --quiet"
+
+    test_cmd "test_res/0xdeadbeef_0x1000_x86.stdout.txt" \
+        "cargo run 2>/dev/null -- 0xdeadbeef 0x1000 x86"
+
+    test_cmd "test_res/config_x86_64_compact.stdout.txt" \
+        "cargo run 2>/dev/null -- --config test_res/config_x86_64.toml --compact"
+
+    test_cmd "test_res/interactive_x86_64_compact.stdout.txt" \
+        "printf '0xdeadbeef\\nfoo\\n0x1000\\n' | cargo run 2>/dev/null -- x86_64 --interactive --compact"
+
+    test_cmd "test_res/radix_hex_strides.stdout.txt" \
+        "cargo run 2>/dev/null -- deadbeef x86_64 --radix hex --strides 1000 | grep stride"
+
+    test_cmd "test_res/radix_hex_from_indices.stdout.txt" \
+        "cargo run 2>/dev/null -- x86_64 --radix hex --from-indices 1ff,0,10,20 --compact"
+
+    test_cmd "test_res/radix_hex_pte_path.stdout.txt" \
+        "cargo run 2>/dev/null -- x86_64 --radix hex --pte 8000000000200083 --pte-level 2 --pte-path 1ff,0,10"
+
+    test_cmd "test_res/radix_hex_cache_line_size.stdout.txt" \
+        "cargo run 2>/dev/null -- deadbeef x86_64 --radix hex --cache-line-size=80 | grep 'cache line'"
 }
 
 function test_cmd() {